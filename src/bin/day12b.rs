@@ -1,126 +1,24 @@
-use std::{
-    collections::{HashMap, HashSet},
-    ops::Add,
-    str::FromStr,
-};
-
-use anyhow::{Error, Result};
-
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
-struct Position {
-    pub i: i32,
-    pub j: i32,
-}
-
-impl Position {
-    const ADJACENTS: [Position; 4] = [
-        Position { i: -1, j: 0 },
-        Position { i: 0, j: 1 },
-        Position { i: 1, j: 0 },
-        Position { i: 0, j: -1 },
-    ];
-
-    pub fn rotate_clockwise(self) -> Position {
-        Position {
-            i: self.j,
-            j: -self.i,
-        }
-    }
-}
-
-impl Add<Position> for Position {
-    type Output = Position;
-
-    fn add(self, other: Position) -> Position {
-        Position {
-            i: self.i + other.i,
-            j: self.j + other.j,
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-struct Problem {
-    map: HashMap<Position, char>,
-}
-
-impl FromStr for Problem {
-    type Err = Error;
+use anyhow::Result;
 
-    fn from_str(input: &str) -> Result<Self> {
-        let mut map = HashMap::new();
-        for (i, line) in input.lines().enumerate() {
-            for (j, c) in line.chars().enumerate() {
-                map.insert(
-                    Position {
-                        i: i as i32,
-                        j: j as i32,
-                    },
-                    c,
-                );
-            }
-        }
-        Ok(Problem { map })
-    }
-}
-
-fn compute_region_price(
-    map: &HashMap<Position, char>,
-    start: Position,
-    c: char,
-    seen: &mut HashSet<Position>,
-) -> Result<u32> {
-    let mut area = 0;
-    let mut sides = 0;
-    let mut stack = vec![start];
-    seen.insert(start);
-
-    while let Some(current) = stack.pop() {
-        area += 1;
-        for adj in Position::ADJACENTS {
-            let next = current + adj;
-            if map.get(&next) != Some(&c) {
-                continue;
-            }
-            if seen.insert(next) {
-                stack.push(next);
-            }
-        }
-        for dir in Position::ADJACENTS {
-            let normal = dir.rotate_clockwise();
-            if map.get(&(current + normal)) != Some(&c)
-                && !(map.get(&(current + dir)) == Some(&c)
-                    && map.get(&(current + dir + normal)) != Some(&c))
-            {
-                sides += 1;
-            }
-        }
-    }
+use aoc2024::day12::{run, Mode, Options, Pricing, Problem};
 
-    Ok(area * sides)
-}
-
-fn solve(problem: &Problem) -> Result<u32> {
-    let mut price = 0;
-    let mut seen = HashSet::new();
-    for (&pos, &c) in &problem.map {
-        if !seen.contains(&pos) {
-            price += compute_region_price(&problem.map, pos, c, &mut seen)?;
-        }
-    }
-    Ok(price)
-}
+const OPTIONS: Options = Options {
+    pricing: Pricing::Sides,
+    mode: Mode::Price,
+};
 
 fn main() -> Result<()> {
+    let options = OPTIONS.parse_args(std::env::args().skip(1))?;
     let input = std::io::read_to_string(std::io::stdin().lock())?;
     let problem: Problem = input.trim().parse()?;
-    let answer = solve(&problem)?;
-    println!("{}", answer);
+    print!("{}", run(&problem, &options)?);
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc2024::day12::solve;
+
     use super::*;
 
     #[test]
@@ -131,7 +29,7 @@ BBCC
 EEEC
 ";
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem)?;
+        let answer = solve(&problem, &OPTIONS)?;
         assert_eq!(answer, 80);
         Ok(())
     }
@@ -145,7 +43,7 @@ OXOXO
 OOOOO
 ";
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem)?;
+        let answer = solve(&problem, &OPTIONS)?;
         assert_eq!(answer, 436);
         Ok(())
     }
@@ -159,7 +57,7 @@ EXXXX
 EEEEE
 ";
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem)?;
+        let answer = solve(&problem, &OPTIONS)?;
         assert_eq!(answer, 236);
         Ok(())
     }
@@ -174,7 +72,7 @@ ABBAAA
 AAAAAA
 ";
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem)?;
+        let answer = solve(&problem, &OPTIONS)?;
         assert_eq!(answer, 368);
         Ok(())
     }
@@ -193,7 +91,7 @@ MIIISIJEEE
 MMMISSJEEE
 ";
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem)?;
+        let answer = solve(&problem, &OPTIONS)?;
         assert_eq!(answer, 1206);
         Ok(())
     }
@@ -1,92 +1,25 @@
-use std::str::FromStr;
+use anyhow::Result;
 
-use anyhow::{Context, Error, Result};
-use itertools::Itertools;
-use regex::Regex;
+use aoc2024::day13::{run, Mode, Options, Problem};
 
-#[derive(Clone, Copy, Debug)]
-struct Point {
-    pub x: i32,
-    pub y: i32,
-}
-
-#[derive(Clone, Debug)]
-struct Machine {
-    pub a: Point,
-    pub b: Point,
-    pub t: Point,
-}
-
-impl FromStr for Machine {
-    type Err = Error;
-
-    fn from_str(input: &str) -> Result<Self> {
-        let pattern = Regex::new(
-            r"^Button A: X\+([0-9]+), Y\+([0-9]+)\nButton B: X\+([0-9]+), Y\+([0-9]+)\nPrize: X=([0-9]+), Y=([0-9]+)$",
-        )?;
-        let c = pattern.captures(input).context("Regex mismatch")?;
-        let ax: i32 = c.get(1).unwrap().as_str().parse()?;
-        let ay: i32 = c.get(2).unwrap().as_str().parse()?;
-        let bx: i32 = c.get(3).unwrap().as_str().parse()?;
-        let by: i32 = c.get(4).unwrap().as_str().parse()?;
-        let tx: i32 = c.get(5).unwrap().as_str().parse()?;
-        let ty: i32 = c.get(6).unwrap().as_str().parse()?;
-        Ok(Machine {
-            a: Point { x: ax, y: ay },
-            b: Point { x: bx, y: by },
-            t: Point { x: tx, y: ty },
-        })
-    }
-}
-
-#[derive(Clone, Debug)]
-struct Problem {
-    machines: Vec<Machine>,
-}
-
-impl FromStr for Problem {
-    type Err = Error;
-
-    fn from_str(input: &str) -> Result<Self> {
-        let machines = input
-            .trim()
-            .split("\n\n")
-            .map(|s| s.parse::<Machine>())
-            .collect::<Result<_>>()?;
-        Ok(Problem { machines })
-    }
-}
-
-fn solve_machine(machine: &Machine) -> Option<i32> {
-    (0..=100)
-        .cartesian_product(0..=100)
-        .filter_map(|(a, b)| {
-            let x = a * machine.a.x + b * machine.b.x;
-            let y = a * machine.a.y + b * machine.b.y;
-            if x == machine.t.x && y == machine.t.y {
-                Some(a * 3 + b)
-            } else {
-                None
-            }
-        })
-        .min()
-}
-
-fn solve(problem: &Problem) -> Result<i32> {
-    let tokens = problem.machines.iter().filter_map(solve_machine).sum();
-    Ok(tokens)
-}
+const OPTIONS: Options = Options {
+    offset: 0,
+    limit: Some(100),
+    mode: Mode::Answer,
+};
 
 fn main() -> Result<()> {
+    let options = OPTIONS.parse_args(std::env::args().skip(1))?;
     let input = std::io::read_to_string(std::io::stdin().lock())?;
     let problem: Problem = input.trim().parse()?;
-    let answer = solve(&problem)?;
-    println!("{}", answer);
+    print!("{}", run(&problem, &options)?);
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc2024::day13::solve;
+
     use super::*;
 
     #[test]
@@ -108,7 +41,7 @@ Button B: X+27, Y+71
 Prize: X=18641, Y=10279
 ";
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem)?;
+        let answer = solve(&problem, &OPTIONS)?;
         assert_eq!(answer, 480);
         Ok(())
     }
@@ -1,80 +1,26 @@
-use std::str::FromStr;
+use anyhow::Result;
 
-use anyhow::{Context, Error, Result};
-use regex::Regex;
-
-#[derive(Clone, Debug)]
-struct Robot {
-    pub x: i32,
-    pub y: i32,
-    pub vx: i32,
-    pub vy: i32,
-}
-
-impl FromStr for Robot {
-    type Err = Error;
-
-    fn from_str(input: &str) -> Result<Self> {
-        let pattern = Regex::new(r"^p=(-?[0-9]+),(-?[0-9]+) v=(-?[0-9]+),(-?[0-9]+)$")?;
-        let c = pattern.captures(input).context("Regex mismatch")?;
-        let x: i32 = c.get(1).unwrap().as_str().parse()?;
-        let y: i32 = c.get(2).unwrap().as_str().parse()?;
-        let vx: i32 = c.get(3).unwrap().as_str().parse()?;
-        let vy: i32 = c.get(4).unwrap().as_str().parse()?;
-        Ok(Robot { x, y, vx, vy })
-    }
-}
-
-impl Robot {
-    pub fn tick(&mut self, w: i32, h: i32) {
-        self.x = (self.x + self.vx).rem_euclid(w);
-        self.y = (self.y + self.vy).rem_euclid(h);
-    }
-}
-
-#[derive(Clone, Debug)]
-struct Problem {
-    pub robots: Vec<Robot>,
-}
-
-impl FromStr for Problem {
-    type Err = Error;
-
-    fn from_str(input: &str) -> Result<Self> {
-        let robots: Vec<Robot> = input
-            .trim()
-            .lines()
-            .map(|s| s.parse::<Robot>())
-            .collect::<Result<_>>()?;
-        Ok(Problem { robots })
-    }
-}
-
-fn compute_safety_factor(robots: &[Robot], w: i32, h: i32) -> usize {
-    let mut counts = [[0; 2]; 2];
-    for robot in robots {
-        if robot.x == w / 2 || robot.y == h / 2 {
-            continue;
-        }
-        counts[(robot.x / ((w + 1) / 2)) as usize][(robot.y / ((h + 1) / 2)) as usize] += 1;
-    }
-    counts[0][0] * counts[0][1] * counts[1][0] * counts[1][1]
-}
+use aoc2024::day14::{compute_safety_factor, Partition, Problem, RunOptions};
 
 fn solve(problem: &Problem, w: i32, h: i32, ticks: usize) -> Result<usize> {
-    let mut robots = problem.robots.clone();
-    for _ in 0..ticks {
-        for robot in &mut robots {
-            robot.tick(w, h);
-        }
-    }
-    Ok(compute_safety_factor(&robots, w, h))
+    let positions = problem.positions_at(ticks, w, h);
+    Ok(compute_safety_factor(&positions, w, h))
 }
 
 fn main() -> Result<()> {
+    let options = RunOptions::default().parse_args(std::env::args().skip(1))?;
     let input = std::io::read_to_string(std::io::stdin().lock())?;
     let problem: Problem = input.trim().parse()?;
-    let answer = solve(&problem, 101, 103, 100)?;
+    let (w, h) = options.arena(&problem)?;
+    if options.heatmap {
+        let (columns, rows) = options.grid;
+        let positions = problem.positions_at(options.ticks, w, h);
+        print!(
+            "{}",
+            Partition::new(&positions, w, h, columns, rows).heatmap()
+        );
+    }
+    let answer = solve(&problem, w, h, options.ticks)?;
     println!("{}", answer);
     Ok(())
 }
@@ -1,119 +1,48 @@
-use std::{
-    cmp::{Ordering, Reverse},
-    collections::BinaryHeap,
-    str::FromStr,
-    vec,
-};
-
-use anyhow::{Context, Error, Result};
-use regex::Regex;
-
-#[derive(Clone, Debug)]
-struct Robot {
-    pub x: i32,
-    pub y: i32,
-    pub vx: i32,
-    pub vy: i32,
+use std::path::PathBuf;
+
+use anyhow::{bail, ensure, Context, Result};
+
+use aoc2024::day14::{export_contact_sheet, export_frames, Problem, Robot, RunOptions};
+
+// Returns the tick in 0..period that minimizes the variance of the given
+// coordinate. The coordinate repeats with the period, so this is where robots
+// line up along that axis.
+fn min_variance_tick(robots: &[Robot], period: i32, coord: impl Fn(&Robot) -> (i32, i32)) -> i32 {
+    let n = robots.len() as i64;
+    (0..period)
+        .min_by_key(|&t| {
+            let (sum, sum_sq) = robots.iter().fold((0i64, 0i64), |(sum, sum_sq), robot| {
+                let (p, v) = coord(robot);
+                let p = (p + v * t).rem_euclid(period) as i64;
+                (sum + p, sum_sq + p * p)
+            });
+            n * sum_sq - sum * sum
+        })
+        .unwrap()
 }
 
-impl FromStr for Robot {
-    type Err = Error;
-
-    fn from_str(input: &str) -> Result<Self> {
-        let pattern = Regex::new(r"^p=(-?[0-9]+),(-?[0-9]+) v=(-?[0-9]+),(-?[0-9]+)$")?;
-        let c = pattern.captures(input).context("Regex mismatch")?;
-        let x: i32 = c.get(1).unwrap().as_str().parse()?;
-        let y: i32 = c.get(2).unwrap().as_str().parse()?;
-        let vx: i32 = c.get(3).unwrap().as_str().parse()?;
-        let vy: i32 = c.get(4).unwrap().as_str().parse()?;
-        Ok(Robot { x, y, vx, vy })
+fn ext_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = ext_gcd(b, a % b);
+        (g, y, x - (a / b) * y)
     }
 }
 
-impl Robot {
-    pub fn tick(&mut self, w: i32, h: i32) {
-        self.x = (self.x + self.vx).rem_euclid(w);
-        self.y = (self.y + self.vy).rem_euclid(h);
-    }
-}
-
-#[derive(Clone, Debug)]
-struct Problem {
-    pub robots: Vec<Robot>,
-}
-
-impl FromStr for Problem {
-    type Err = Error;
-
-    fn from_str(input: &str) -> Result<Self> {
-        let robots: Vec<Robot> = input
-            .trim()
-            .lines()
-            .map(|s| s.parse::<Robot>())
-            .collect::<Result<_>>()?;
-        Ok(Problem { robots })
-    }
-}
-
-fn niceness(robots: &[Robot], w: i32, h: i32) -> usize {
-    let mut canvas = vec![vec!['.'; w as usize]; h as usize];
-    for robot in robots {
-        canvas[robot.y as usize][robot.x as usize] = '#';
-    }
-
-    let mut niceness = 0;
-    for x in 1..(w - 1) {
-        for y in 1..(h - 1) {
-            if canvas[y as usize][x as usize] == '#' {
-                let mut nice = false;
-                for dx in -1..=1 {
-                    for dy in -1..=1 {
-                        if canvas[(y + dy) as usize][(x + dx) as usize] == '#' {
-                            nice = true;
-                        }
-                    }
-                }
-                if nice {
-                    niceness += 1;
-                }
-            }
-        }
-    }
-
-    niceness
-}
-
-#[derive(Clone, Debug)]
-struct Entry {
-    pub niceness: usize,
-    pub tick: usize,
-    pub robots: Vec<Robot>,
-}
-
-impl PartialEq for Entry {
-    fn eq(&self, other: &Self) -> bool {
-        self.niceness == other.niceness
-    }
-}
-
-impl Eq for Entry {}
-
-impl PartialOrd for Entry {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl Ord for Entry {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.niceness.cmp(&other.niceness)
-    }
+// Solves t = a (mod m), t = b (mod n) for the smallest non-negative t.
+fn crt(a: i64, m: i64, b: i64, n: i64) -> Result<i64> {
+    let (g, p, _) = ext_gcd(m, n);
+    ensure!((b - a) % g == 0, "No tick satisfies both periods");
+    let l = m / g * n;
+    let k = ((b - a) / g * p).rem_euclid(n / g);
+    Ok((a + m * k).rem_euclid(l))
 }
 
-fn draw(robots: &[Robot], w: i32, h: i32, tick: usize) {
+fn draw(positions: &[(i32, i32)], w: i32, h: i32, tick: usize) {
     let mut canvas = vec![vec!['.'; w as usize]; h as usize];
-    for robot in robots {
-        canvas[robot.y as usize][robot.x as usize] = '#';
+    for &(x, y) in positions {
+        canvas[y as usize][x as usize] = '#';
     }
     println!(
         "======================================================== {}",
@@ -125,36 +54,144 @@ fn draw(robots: &[Robot], w: i32, h: i32, tick: usize) {
     println!();
 }
 
-fn tick_all(robots: &mut [Robot], w: i32, h: i32) {
-    for robot in robots {
-        robot.tick(w, h);
-    }
+fn solve(problem: &Problem, w: i32, h: i32) -> Result<usize> {
+    let tx = min_variance_tick(&problem.robots, w, |r| (r.x, r.vx));
+    let ty = min_variance_tick(&problem.robots, h, |r| (r.y, r.vy));
+    let tick = crt(tx as i64, w as i64, ty as i64, h as i64)?;
+    Ok(tick as usize)
+}
+
+#[derive(Clone, Debug, Default)]
+struct Options {
+    pub run: RunOptions,
+    pub draw: bool,
+    pub export_dir: Option<PathBuf>,
+    pub sheet: Option<PathBuf>,
+    pub from: usize,
+    pub to: Option<usize>,
+    pub columns: Option<usize>,
 }
 
-fn solve(problem: &Problem, w: i32, h: i32) -> Result<()> {
-    let mut robots = problem.robots.clone();
-    let mut ranked = BinaryHeap::new();
-    for tick in 0..10000 {
-        ranked.push(Reverse(Entry {
-            niceness: niceness(&robots, w, h),
-            tick,
-            robots: robots.clone(),
-        }));
-        if ranked.len() > 10 {
-            ranked.pop();
+impl Options {
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("Missing {} value", arg))
+            };
+            match arg.as_str() {
+                "--draw" => options.draw = true,
+                "--export" => options.export_dir = Some(PathBuf::from(value()?)),
+                "--sheet" => options.sheet = Some(PathBuf::from(value()?)),
+                "--from" => options.from = value()?.parse()?,
+                "--to" => options.to = Some(value()?.parse()?),
+                "--columns" => options.columns = Some(value()?.parse()?),
+                // Only the arena is used here; the rest is for the safety
+                // factor report of part a.
+                "--ticks" | "--grid" | "--heatmap" => {
+                    bail!("{} is not supported by this part", arg)
+                }
+                _ => {
+                    if !options.run.parse_arg(&arg, &mut args)? {
+                        bail!("Unknown argument: {}", arg);
+                    }
+                }
+            }
         }
-        tick_all(&mut robots, w, h);
-    }
-    let bests = ranked.into_sorted_vec();
-    for best in bests {
-        draw(&best.0.robots, w, h, best.0.tick);
+        if let Some(to) = options.to {
+            ensure!(
+                options.from <= to,
+                "--from {} is after --to {}",
+                options.from,
+                to
+            );
+        }
+        Ok(options)
     }
-    Ok(())
 }
 
 fn main() -> Result<()> {
+    let options = Options::from_args(std::env::args().skip(1))?;
     let input = std::io::read_to_string(std::io::stdin().lock())?;
     let problem: Problem = input.trim().parse()?;
-    solve(&problem, 101, 103)?;
+    let (w, h) = options.run.arena(&problem)?;
+
+    if options.export_dir.is_some() || options.sheet.is_some() {
+        let to = options.to.unwrap_or((w * h - 1) as usize);
+        ensure!(
+            options.from <= to,
+            "--from {} is past the last tick",
+            options.from
+        );
+        let ticks: Vec<usize> = (options.from..=to).collect();
+        if let Some(dir) = &options.export_dir {
+            export_frames(&problem, w, h, &ticks, dir)?;
+        }
+        if let Some(path) = &options.sheet {
+            let columns = options.columns.unwrap_or(100);
+            export_contact_sheet(&problem, w, h, &ticks, columns, path)?;
+        }
+        return Ok(());
+    }
+
+    let answer = solve(&problem, w, h)?;
+    if options.draw {
+        draw(&problem.positions_at(answer, w, h), w, h, answer);
+    }
+    println!("{}", answer);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args() -> Result<()> {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let options = Options::from_args(args(&["--width", "11", "--from", "2", "--to", "5"]))?;
+        assert_eq!(options.run.width, Some(11));
+        assert_eq!((options.from, options.to), (2, Some(5)));
+        assert!(Options::from_args(args(&["--heatmap"])).is_err());
+        assert!(Options::from_args(args(&["--ticks", "10"])).is_err());
+        assert!(Options::from_args(args(&["--from", "5", "--to", "2"])).is_err());
+        // A zero period would leave no tick to pick.
+        assert!(Options::from_args(args(&["--height", "0"])).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn converge() -> Result<()> {
+        let (w, h, tick) = (101, 103, 1234);
+        let mut seed: u64 = 1;
+        let mut random = |m: i32| {
+            seed = (seed * 1103515245 + 12345) % (1 << 31);
+            ((seed >> 8) % m as u64) as i32
+        };
+        let robots = (0..100)
+            .map(|_| {
+                let (vx, vy) = (random(201) - 100, random(201) - 100);
+                let (x, y) = (50 + random(5), 40 + random(5));
+                Robot {
+                    x: (x - vx * tick).rem_euclid(w),
+                    y: (y - vy * tick).rem_euclid(h),
+                    vx,
+                    vy,
+                }
+            })
+            .collect();
+        let problem = Problem { robots };
+        assert_eq!(solve(&problem, w, h)?, tick as usize);
+        Ok(())
+    }
+
+    #[test]
+    fn chinese_remainder() -> Result<()> {
+        assert_eq!(crt(2, 3, 3, 5)?, 8);
+        assert_eq!(crt(1, 4, 3, 6)?, 9);
+        assert!(crt(0, 4, 1, 6).is_err());
+        Ok(())
+    }
+}
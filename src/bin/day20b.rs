@@ -1,142 +1,25 @@
-use std::{
-    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
-    ops::Add,
-    str::FromStr,
-};
-
-use anyhow::{bail, ensure, Context, Error, Result};
-
-#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
-struct Point {
-    pub i: isize,
-    pub j: isize,
-}
-
-impl Point {
-    pub const RIGHT: Point = Point { i: 0, j: 1 };
-    pub const DOWN: Point = Point { i: 1, j: 0 };
-    pub const LEFT: Point = Point { i: 0, j: -1 };
-    pub const UP: Point = Point { i: -1, j: 0 };
-    pub const DIRS: [Point; 4] = [Point::RIGHT, Point::DOWN, Point::LEFT, Point::UP];
-
-    pub fn new(i: isize, j: isize) -> Self {
-        Point { i, j }
-    }
-}
-
-impl Add<Point> for Point {
-    type Output = Point;
-
-    fn add(self, other: Point) -> Point {
-        Point {
-            i: self.i + other.i,
-            j: self.j + other.j,
-        }
-    }
-}
-
-#[derive(Clone, Debug)]
-struct Problem {
-    pub map: HashSet<Point>,
-    pub start: Point,
-    pub goal: Point,
-}
-
-impl FromStr for Problem {
-    type Err = Error;
-
-    fn from_str(input: &str) -> Result<Self> {
-        let mut map: HashSet<Point> = HashSet::new();
-        let mut starts: Vec<Point> = Vec::new();
-        let mut goals: Vec<Point> = Vec::new();
-        for (i, line) in input.lines().enumerate() {
-            for (j, c) in line.chars().enumerate() {
-                let p = Point::new(i as isize, j as isize);
-                match c {
-                    '.' => {
-                        map.insert(p);
-                    }
-                    'S' => {
-                        starts.push(p);
-                        map.insert(p);
-                    }
-                    'E' => {
-                        goals.push(p);
-                        map.insert(p);
-                    }
-                    '#' => {}
-                    _ => bail!("Invalid character: {}", c),
-                }
-            }
-        }
+use anyhow::Result;
 
-        ensure!(starts.len() == 1, "Expected exactly one start");
-        ensure!(goals.len() == 1, "Expected exactly one goal");
+use aoc2024::day20::{run, Mode, Options, Problem};
 
-        let start = starts[0];
-        let goal = goals[0];
-
-        Ok(Problem { map, start, goal })
-    }
-}
-
-fn bfs(map: &HashSet<Point>, start: Point) -> HashMap<Point, usize> {
-    let mut queue = VecDeque::from([(start, 0)]);
-    let mut distances = HashMap::from([(start, 0)]);
-
-    while let Some((current_pos, current_dist)) = queue.pop_front() {
-        for dir in Point::DIRS {
-            let next_pos = current_pos + dir;
-            let next_dist = current_dist + 1;
-            if !map.contains(&next_pos) {
-                continue;
-            }
-            if let Entry::Vacant(entry) = distances.entry(next_pos) {
-                entry.insert(next_dist);
-                queue.push_back((next_pos, next_dist));
-            }
-        }
-    }
-
-    distances
-}
-
-fn solve(problem: &Problem, min_save: usize) -> Result<usize> {
-    let from_start = bfs(&problem.map, problem.start);
-    let from_goal = bfs(&problem.map, problem.goal);
-
-    let mut count = 0;
-    let original_distance = *from_start.get(&problem.goal).context("No path found")?;
-    for &cheat_start in from_start.keys() {
-        for &cheat_end in from_start.keys() {
-            let cheat_len = (cheat_end.i - cheat_start.i).unsigned_abs()
-                + (cheat_end.j - cheat_start.j).unsigned_abs();
-            if cheat_len > 20 {
-                continue;
-            }
-            let cheat_distance = from_start[&cheat_start] + cheat_len + from_goal[&cheat_end];
-            if cheat_distance < original_distance {
-                let save = original_distance - cheat_distance;
-                if save >= min_save {
-                    count += 1;
-                }
-            }
-        }
-    }
-
-    Ok(count)
-}
+const OPTIONS: Options = Options {
+    radius: 20,
+    min_save: 100,
+    mode: Mode::Count,
+};
 
 fn main() -> Result<()> {
+    let options = OPTIONS.parse_args(std::env::args().skip(1))?;
     let input = std::io::read_to_string(std::io::stdin().lock())?;
     let problem: Problem = input.parse()?;
-    let answer = solve(&problem, 100)?;
-    println!("{}", answer);
+    print!("{}", run(&problem, &options)?);
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc2024::day20::solve;
+
     use super::*;
 
     #[test]
@@ -158,7 +41,13 @@ mod tests {
 ###############
 ";
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem, 72)?;
+        let answer = solve(
+            &problem,
+            &Options {
+                min_save: 72,
+                ..OPTIONS
+            },
+        )?;
         assert_eq!(answer, 29);
         Ok(())
     }
@@ -1,16 +1,11 @@
-use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
-};
-
-use anyhow::{Context, Error, Result};
+use std::str::FromStr;
 
-type Node = String;
+use anyhow::{bail, Context, Error, Result};
 
-#[derive(Clone, Debug)]
-struct Graph {
-    pub edges: HashMap<Node, HashSet<Node>>,
-}
+use aoc2024::day23::{
+    kclique::{CliqueFilter, NamePattern, Quantifier},
+    Graph,
+};
 
 #[derive(Clone, Debug)]
 struct Problem {
@@ -21,52 +16,69 @@ impl FromStr for Problem {
     type Err = Error;
 
     fn from_str(input: &str) -> Result<Self> {
-        let mut edges: HashMap<Node, HashSet<Node>> = HashMap::new();
-        for line in input.lines() {
-            let (a, b) = line.split_once('-').context("Invalid edge")?;
-            edges
-                .entry(a.to_string())
-                .or_default()
-                .insert(b.to_string());
-            edges
-                .entry(b.to_string())
-                .or_default()
-                .insert(a.to_string());
-        }
         Ok(Problem {
-            graph: Graph { edges },
+            graph: input.parse()?,
         })
     }
 }
 
-fn solve(problem: &Problem) -> Result<usize> {
-    let mut count = 0;
-    let edges = &problem.graph.edges;
-    for (a, bs) in edges {
-        for b in bs {
-            if b <= a {
-                continue;
-            }
-            for c in edges.get(b).unwrap() {
-                if c <= b {
-                    continue;
+#[derive(Clone, Debug)]
+struct Options {
+    pub size: usize,
+    pub filter: CliqueFilter,
+    pub list: bool,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            size: 3,
+            filter: CliqueFilter::new(NamePattern::prefix("t"), Quantifier::AtLeastOne),
+            list: false,
+        }
+    }
+}
+
+impl Options {
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut options = Options::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--size" => {
+                    options.size = args.next().context("Missing --size value")?.parse()?;
                 }
-                if !bs.contains(c) {
-                    continue;
+                "--prefix" => {
+                    let prefix = args.next().context("Missing --prefix value")?;
+                    options.filter.pattern = NamePattern::prefix(&prefix);
                 }
-                if a.starts_with('t') || b.starts_with('t') || c.starts_with('t') {
-                    count += 1;
+                "--regex" => {
+                    let pattern = args.next().context("Missing --regex value")?;
+                    options.filter.pattern = NamePattern::regex(&pattern)?;
                 }
+                "--all" => options.filter.quantifier = Quantifier::All,
+                "--list" => options.list = true,
+                _ => bail!("Unknown argument: {}", arg),
             }
         }
+        Ok(options)
     }
-    Ok(count)
+}
+
+fn solve(problem: &Problem, options: &Options) -> Result<usize> {
+    Ok(problem.graph.count_k_cliques(options.size, &options.filter))
 }
 
 fn main() -> Result<()> {
+    let options = Options::from_args(std::env::args().skip(1))?;
     let input = std::io::read_to_string(std::io::stdin().lock())?;
     let problem: Problem = input.parse()?;
-    let answer = solve(&problem)?;
+    if options.list {
+        for clique in problem.graph.k_cliques(options.size, &options.filter) {
+            println!("{}", clique.join(","));
+        }
+    }
+    let answer = solve(&problem, &options)?;
     println!("{}", answer);
     Ok(())
 }
@@ -111,7 +123,7 @@ tb-vc
 td-yn
 ";
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem)?;
+        let answer = solve(&problem, &Options::default())?;
         assert_eq!(answer, 7);
         Ok(())
     }
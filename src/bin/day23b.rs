@@ -1,16 +1,8 @@
-use std::{
-    collections::{HashMap, HashSet},
-    str::FromStr,
-};
+use std::str::FromStr;
 
-use anyhow::{Context, Error, Result};
+use anyhow::{bail, Error, Result};
 
-type Node = String;
-
-#[derive(Clone, Debug)]
-struct Graph {
-    pub edges: HashMap<Node, HashSet<Node>>,
-}
+use aoc2024::day23::{report::Report, Graph};
 
 #[derive(Clone, Debug)]
 struct Problem {
@@ -21,78 +13,43 @@ impl FromStr for Problem {
     type Err = Error;
 
     fn from_str(input: &str) -> Result<Self> {
-        let mut edges: HashMap<Node, HashSet<Node>> = HashMap::new();
-        for line in input.lines() {
-            let (a, b) = line.split_once('-').context("Invalid edge")?;
-            edges
-                .entry(a.to_string())
-                .or_default()
-                .insert(b.to_string());
-            edges
-                .entry(b.to_string())
-                .or_default()
-                .insert(a.to_string());
-        }
         Ok(Problem {
-            graph: Graph { edges },
+            graph: input.parse()?,
         })
     }
 }
 
-fn naive_search<'a>(
-    chosen: &[&'a str],
-    candidates: &HashSet<&'a str>,
-    edges: &HashMap<&str, HashSet<&'a str>>,
-    known_best: usize,
-) -> Vec<&'a str> {
-    if chosen.len() + candidates.len() <= known_best {
-        return Vec::new();
-    }
-    if candidates.is_empty() {
-        return chosen.to_vec();
-    }
-
-    let mut best = Vec::new();
-    for &next in candidates {
-        let new_chosen: Vec<&str> = chosen.iter().copied().chain([next]).collect();
-        let new_candidates: HashSet<&str> =
-            candidates.intersection(&edges[next]).copied().collect();
-        let new_best = naive_search(
-            &new_chosen,
-            &new_candidates,
-            edges,
-            known_best.max(best.len()),
-        );
-        if new_best.len() > best.len() {
-            best = new_best;
-        }
-    }
-    best
-}
-
 fn solve(problem: &Problem) -> Result<String> {
-    let edges = &problem.graph.edges;
-    let edges: HashMap<&str, HashSet<&str>> = edges
-        .iter()
-        .map(|(a, bs)| (a.as_str(), bs.iter().map(|b| b.as_str()).collect()))
-        .collect();
+    Ok(problem.graph.maximum_clique().join(","))
+}
 
-    let mut best = Vec::new();
-    for (a, bs) in &edges {
-        let new_best = naive_search(&[a], bs, &edges, best.len());
-        if new_best.len() > best.len() {
-            best = new_best;
-        }
-    }
-    best.sort();
-    Ok(best.join(","))
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Output {
+    Answer,
+    Report,
+    Dot,
+    Json,
 }
 
 fn main() -> Result<()> {
+    let output = match std::env::args().nth(1).as_deref() {
+        None => Output::Answer,
+        Some("--report") => Output::Report,
+        Some("--dot") => Output::Dot,
+        Some("--json") => Output::Json,
+        Some(arg) => bail!("Unknown argument: {}", arg),
+    };
     let input = std::io::read_to_string(std::io::stdin().lock())?;
     let problem: Problem = input.parse()?;
-    let answer = solve(&problem)?;
-    println!("{}", answer);
+    match output {
+        Output::Answer => {
+            let answer = solve(&problem)?;
+            println!("{}", answer);
+        }
+        Output::Report => print!("{}", Report::new(&problem.graph)),
+        Output::Dot => print!("{}", problem.graph.to_dot(&problem.graph.maximum_clique())),
+        Output::Json => print!("{}", problem.graph.to_json()),
+    }
     Ok(())
 }
 
@@ -1,72 +1,26 @@
-use std::str::FromStr;
+use std::borrow::Cow;
 
-use anyhow::{Context, Error, Result};
+use anyhow::Result;
 
-#[derive(Clone, Debug)]
-struct Equation {
-    target: u64,
-    factors: Vec<u64>,
-}
-
-#[derive(Clone, Debug)]
-struct Problem {
-    equations: Vec<Equation>,
-}
-
-impl FromStr for Problem {
-    type Err = Error;
-
-    fn from_str(input: &str) -> Result<Self> {
-        let equations = input
-            .trim()
-            .lines()
-            .map(|line| {
-                let (target_str, factors_str) = line.split_once(": ").context("Invalid input")?;
-                let target = target_str.parse()?;
-                let factors = factors_str
-                    .split_ascii_whitespace()
-                    .map(|x| x.parse::<u64>())
-                    .collect::<Result<Vec<_>, _>>()?;
-                Ok(Equation { target, factors })
-            })
-            .collect::<Result<_>>()?;
-        Ok(Problem { equations })
-    }
-}
+use aoc2024::day7::{run, Mode, Options, Problem};
 
-fn search(current: u64, target: u64, factors: &[u64]) -> bool {
-    if current > target {
-        return false;
-    }
-    let Some((&first, new_factors)) = factors.split_first() else {
-        return current == target;
-    };
-    search(current + first, target, new_factors) || search(current * first, target, new_factors)
-}
-
-fn can_produce(equation: &Equation) -> bool {
-    search(equation.factors[0], equation.target, &equation.factors[1..])
-}
-
-fn solve(problem: &Problem) -> Result<u64> {
-    Ok(problem
-        .equations
-        .iter()
-        .filter(|equation| can_produce(equation))
-        .map(|equation| equation.target)
-        .sum())
-}
+const OPTIONS: Options = Options {
+    operators: Cow::Borrowed("+ *"),
+    mode: Mode::Sum,
+};
 
 fn main() -> Result<()> {
+    let options = OPTIONS.parse_args(std::env::args().skip(1))?;
     let input = std::io::read_to_string(std::io::stdin().lock())?;
     let problem: Problem = input.parse()?;
-    let answer = solve(&problem)?;
-    println!("{}", answer);
+    print!("{}", run(&problem, &options)?);
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use aoc2024::day7::solve;
+
     use super::*;
 
     #[test]
@@ -82,7 +36,7 @@ mod tests {
 292: 11 6 16 20
 "#;
         let problem: Problem = input.parse()?;
-        let answer = solve(&problem)?;
+        let answer = solve(&problem, &OPTIONS)?;
         assert_eq!(answer, 3749);
         Ok(())
     }
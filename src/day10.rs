@@ -0,0 +1,453 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    ops::Add,
+    str::FromStr,
+};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+
+// Upper bound on the search steps taken when trails may revisit heights and
+// have to be enumerated one by one.
+pub const MAX_STEPS: usize = 10_000_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Position {
+    pub i: i32,
+    pub j: i32,
+}
+
+impl Position {
+    pub const ADJACENTS: [Position; 4] = [
+        Position { i: -1, j: 0 },
+        Position { i: 0, j: 1 },
+        Position { i: 1, j: 0 },
+        Position { i: 0, j: -1 },
+    ];
+}
+
+impl Add<Position> for Position {
+    type Output = Position;
+
+    fn add(self, other: Position) -> Position {
+        Position {
+            i: self.i + other.i,
+            j: self.j + other.j,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub map: HashMap<Position, u32>,
+    pub height: i32,
+    pub width: i32,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut map = HashMap::new();
+        let (mut height, mut width) = (0, 0);
+        for (i, line) in input.lines().enumerate() {
+            for (j, c) in line.chars().enumerate() {
+                let h = c.to_digit(10).context("Not a digit")?;
+                map.insert(
+                    Position {
+                        i: i as i32,
+                        j: j as i32,
+                    },
+                    h,
+                );
+                width = width.max(j as i32 + 1);
+            }
+            height = i as i32 + 1;
+        }
+        Ok(Problem { map, height, width })
+    }
+}
+
+// Height change allowed for a single step.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StepRule {
+    Exactly(i32),
+    UpOrDown(i32),
+    Between(i32, i32),
+}
+
+impl StepRule {
+    pub fn allows(self, from: u32, to: u32) -> bool {
+        let delta = to as i64 - from as i64;
+        match self {
+            StepRule::Exactly(k) => delta == k as i64,
+            StepRule::UpOrDown(k) => delta.abs() == k as i64,
+            StepRule::Between(lo, hi) => (lo as i64..=hi as i64).contains(&delta),
+        }
+    }
+
+    // Whether every step strictly climbs (or strictly descends), so that
+    // trails can never visit a cell twice.
+    pub fn is_monotonic(self) -> bool {
+        match self {
+            StepRule::Exactly(k) => k != 0,
+            StepRule::UpOrDown(_) => false,
+            StepRule::Between(lo, hi) => lo > 0 || hi < 0,
+        }
+    }
+}
+
+// Accepts "+1" or "1", "+-1" or "±1", "<=3" (climb by 1 to 3) and "-1..2".
+impl FromStr for StepRule {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let rule = if let Some(k) = s.strip_prefix("+-").or_else(|| s.strip_prefix('±')) {
+            StepRule::UpOrDown(k.parse()?)
+        } else if let Some(k) = s.strip_prefix("<=") {
+            let k: i32 = k.trim_start_matches('+').parse()?;
+            ensure!(k >= 1, "Step limit must be positive");
+            StepRule::Between(1, k)
+        } else if let Some((lo, hi)) = s.split_once("..") {
+            let (lo, hi) = (lo.parse()?, hi.parse()?);
+            ensure!(lo <= hi, "Empty step range: {}", s);
+            StepRule::Between(lo, hi)
+        } else {
+            StepRule::Exactly(s.trim_start_matches('+').parse()?)
+        };
+        Ok(rule)
+    }
+}
+
+pub type Trail = Vec<Position>;
+
+// A trail starts at a cell of `start` height and follows the step rule until
+// it first reaches a cell of `end` height, never visiting a cell twice.
+#[derive(Clone, Copy, Debug)]
+pub struct Explorer<'a> {
+    pub problem: &'a Problem,
+    pub rule: StepRule,
+    pub start: u32,
+    pub end: u32,
+}
+
+impl<'a> Explorer<'a> {
+    pub fn new(problem: &'a Problem, rule: StepRule, start: u32, end: u32) -> Result<Self> {
+        ensure!(start != end, "Start and end heights must differ");
+        Ok(Explorer {
+            problem,
+            rule,
+            start,
+            end,
+        })
+    }
+
+    pub fn trailheads(&self) -> Vec<Position> {
+        let mut heads: Vec<Position> = self
+            .problem
+            .map
+            .iter()
+            .filter(|(_, &h)| h == self.start)
+            .map(|(&pos, _)| pos)
+            .collect();
+        heads.sort();
+        heads
+    }
+
+    fn steps(&self, pos: Position) -> impl Iterator<Item = Position> + '_ {
+        let height = self.problem.map[&pos];
+        Position::ADJACENTS
+            .into_iter()
+            .map(move |adj| pos + adj)
+            .filter(move |next| {
+                self.problem
+                    .map
+                    .get(next)
+                    .is_some_and(|&h| self.rule.allows(height, h))
+            })
+    }
+
+    // Number of distinct end cells reachable from the trailhead.
+    pub fn score(&self, head: Position) -> usize {
+        let mut seen = HashSet::from([head]);
+        let mut stack = vec![head];
+        let mut score = 0;
+        while let Some(current) = stack.pop() {
+            for next in self.steps(current) {
+                if !seen.insert(next) {
+                    continue;
+                }
+                if self.problem.map[&next] == self.end {
+                    score += 1;
+                } else {
+                    stack.push(next);
+                }
+            }
+        }
+        score
+    }
+
+    // Number of distinct trails from the trailhead.
+    pub fn rating(&self, head: Position) -> Result<u64> {
+        if self.rule.is_monotonic() {
+            return Ok(self.count_monotonic(head, &mut HashMap::new()));
+        }
+        let mut rating = 0;
+        self.walk(head, &mut |_| rating += 1)?;
+        Ok(rating)
+    }
+
+    fn count_monotonic(&self, pos: Position, memo: &mut HashMap<Position, u64>) -> u64 {
+        if self.problem.map[&pos] == self.end {
+            return 1;
+        }
+        if let Some(&count) = memo.get(&pos) {
+            return count;
+        }
+        let count = self
+            .steps(pos)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|next| self.count_monotonic(next, memo))
+            .sum();
+        memo.insert(pos, count);
+        count
+    }
+
+    // Every trail from the trailhead, in lexicographic order of positions.
+    pub fn trails(&self, head: Position) -> Result<Vec<Trail>> {
+        let mut trails = Vec::new();
+        self.walk(head, &mut |trail| trails.push(trail.to_vec()))?;
+        trails.sort();
+        Ok(trails)
+    }
+
+    fn walk(&self, head: Position, visit: &mut impl FnMut(&[Position])) -> Result<()> {
+        let mut trail = vec![head];
+        let mut on_trail = HashSet::from([head]);
+        let mut steps = 0;
+        self.extend(&mut trail, &mut on_trail, &mut steps, visit)
+    }
+
+    fn extend(
+        &self,
+        trail: &mut Trail,
+        on_trail: &mut HashSet<Position>,
+        steps: &mut usize,
+        visit: &mut impl FnMut(&[Position]),
+    ) -> Result<()> {
+        *steps += 1;
+        ensure!(
+            *steps <= MAX_STEPS,
+            "Gave up after {} search steps",
+            MAX_STEPS
+        );
+        let current = *trail.last().unwrap();
+        if trail.len() > 1 && self.problem.map[&current] == self.end {
+            visit(trail);
+            return Ok(());
+        }
+        for next in self.steps(current).collect::<Vec<_>>() {
+            if on_trail.insert(next) {
+                trail.push(next);
+                self.extend(trail, on_trail, steps, visit)?;
+                trail.pop();
+                on_trail.remove(&next);
+            }
+        }
+        Ok(())
+    }
+
+    // Shows the heights along the trail, with every other cell blanked out.
+    pub fn render(&self, trail: &[Position]) -> String {
+        let cells: HashSet<&Position> = trail.iter().collect();
+        let mut output = String::new();
+        for i in 0..self.problem.height {
+            for j in 0..self.problem.width {
+                let pos = Position { i, j };
+                match self.problem.map.get(&pos) {
+                    Some(h) if cells.contains(&pos) => write!(output, "{}", h).unwrap(),
+                    _ => output.push('.'),
+                }
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Metric {
+    Score,
+    Rating,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Total,
+    Trailheads,
+    List(usize),
+    Show(usize, usize),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub metric: Metric,
+    pub rule: StepRule,
+    pub start: u32,
+    pub end: u32,
+    pub mode: Mode,
+}
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--step" => self.rule = args.next().context("Missing --step value")?.parse()?,
+                "--start" => self.start = args.next().context("Missing --start value")?.parse()?,
+                "--end" => self.end = args.next().context("Missing --end value")?.parse()?,
+                "--trailheads" => self.mode = Mode::Trailheads,
+                "--list" => {
+                    let head = args.next().context("Missing --list value")?.parse()?;
+                    self.mode = Mode::List(head);
+                }
+                "--show" => {
+                    let head = args.next().context("Missing --show trailhead")?.parse()?;
+                    let trail = args.next().context("Missing --show trail")?.parse()?;
+                    self.mode = Mode::Show(head, trail);
+                }
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+}
+
+fn format_trail(trail: &[Position]) -> String {
+    trail
+        .iter()
+        .map(|pos| format!("({},{})", pos.i, pos.j))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<u64> {
+    let explorer = Explorer::new(problem, options.rule, options.start, options.end)?;
+    let mut total = 0;
+    for head in explorer.trailheads() {
+        total += match options.metric {
+            Metric::Score => explorer.score(head) as u64,
+            Metric::Rating => explorer.rating(head)?,
+        };
+    }
+    Ok(total)
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let explorer = Explorer::new(problem, options.rule, options.start, options.end)?;
+    let heads = explorer.trailheads();
+    let mut output = String::new();
+    match options.mode {
+        Mode::Total => writeln!(output, "{}", solve(problem, options)?)?,
+        Mode::Trailheads => {
+            for (index, &head) in heads.iter().enumerate() {
+                writeln!(
+                    output,
+                    "{}: ({},{}) score {}, rating {}",
+                    index,
+                    head.i,
+                    head.j,
+                    explorer.score(head),
+                    explorer.rating(head)?
+                )?;
+            }
+        }
+        Mode::List(index) => {
+            let head = *heads.get(index).context("No such trailhead")?;
+            for trail in explorer.trails(head)? {
+                writeln!(output, "{}", format_trail(&trail))?;
+            }
+        }
+        Mode::Show(index, rank) => {
+            let head = *heads.get(index).context("No such trailhead")?;
+            let trails = explorer.trails(head)?;
+            let trail = trails.get(rank).context("No such trail")?;
+            writeln!(output, "{}", format_trail(trail))?;
+            output.push_str(&explorer.render(trail));
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "89010123
+78121874
+87430965
+96549874
+45678903
+32019012
+01329801
+10456732
+";
+
+    #[test]
+    fn trails() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        let explorer = Explorer::new(&problem, StepRule::Exactly(1), 0, 9)?;
+        let heads = explorer.trailheads();
+        assert_eq!(heads.len(), 9);
+        assert_eq!(heads[0], Position { i: 0, j: 2 });
+        assert_eq!(explorer.score(heads[0]), 5);
+        assert_eq!(explorer.rating(heads[0])?, 20);
+
+        let trails = explorer.trails(heads[0])?;
+        assert_eq!(trails.len(), 20);
+        assert!(trails.iter().all(|trail| trail.len() == 10));
+        assert_eq!(
+            explorer.render(&trails[0]),
+            ".901....
+.8.2....
+.743....
+.65.....
+........
+........
+........
+........
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn rules() -> Result<()> {
+        assert_eq!("+1".parse::<StepRule>()?, StepRule::Exactly(1));
+        assert_eq!("±1".parse::<StepRule>()?, StepRule::UpOrDown(1));
+        assert_eq!("<=+2".parse::<StepRule>()?, StepRule::Between(1, 2));
+        assert_eq!("-1..1".parse::<StepRule>()?, StepRule::Between(-1, 1));
+        assert!("2..1".parse::<StepRule>().is_err());
+
+        let problem: Problem = "013\n123\n".parse()?;
+        let head = Position { i: 0, j: 0 };
+        let explorer = Explorer::new(&problem, StepRule::Exactly(1), 0, 3)?;
+        assert_eq!(explorer.rating(head)?, 2);
+        assert_eq!(explorer.score(head), 1);
+        let explorer = Explorer::new(&problem, StepRule::Between(1, 2), 0, 3)?;
+        assert_eq!(explorer.rating(head)?, 3);
+        assert_eq!(explorer.score(head), 2);
+
+        // Descending is allowed, but trails stop at the first 3.
+        let explorer = Explorer::new(&problem, StepRule::UpOrDown(1), 0, 3)?;
+        assert_eq!(explorer.rating(head)?, explorer.trails(head)?.len() as u64);
+        assert!(explorer
+            .trails(head)?
+            .iter()
+            .all(|trail| trail[1..trail.len() - 1]
+                .iter()
+                .all(|pos| problem.map[pos] != 3)));
+        Ok(())
+    }
+}
@@ -0,0 +1,267 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::{bail, Context, Error, Result};
+
+pub mod rules;
+
+use rules::Rules;
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub stones: Vec<u64>,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let stones: Vec<u64> = input
+            .trim()
+            .split_ascii_whitespace()
+            .map(|s| s.parse())
+            .collect::<Result<_, _>>()?;
+        Ok(Problem { stones })
+    }
+}
+
+// Number of stones for each engraved value. The order of stones does not
+// affect how they evolve, so this is all that needs tracking.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Stones {
+    pub counts: HashMap<u64, u128>,
+    pub generation: usize,
+}
+
+impl Stones {
+    pub fn new(stones: &[u64]) -> Self {
+        let mut counts = HashMap::new();
+        for &n in stones {
+            *counts.entry(n).or_default() += 1;
+        }
+        Stones {
+            counts,
+            generation: 0,
+        }
+    }
+
+    pub fn blink(&mut self, rules: &Rules) -> Result<()> {
+        let mut next: HashMap<u64, u128> = HashMap::with_capacity(self.counts.len());
+        for (&n, &count) in &self.counts {
+            for m in rules.apply(n)? {
+                let entry = next.entry(m).or_default();
+                *entry = entry.checked_add(count).with_context(|| {
+                    format!("Stone count overflows after {} blinks", self.generation + 1)
+                })?;
+            }
+        }
+        self.counts = next;
+        self.generation += 1;
+        Ok(())
+    }
+
+    pub fn total(&self) -> Result<u128> {
+        self.counts
+            .values()
+            .try_fold(0u128, |total, &count| total.checked_add(count))
+            .context("Total stone count overflows")
+    }
+
+    pub fn distinct(&self) -> usize {
+        self.counts.len()
+    }
+
+    // Most common values first; ties are broken by the smaller value.
+    pub fn top(&self, k: usize) -> Vec<(u64, u128)> {
+        let mut counts: Vec<(u64, u128)> = self.counts.iter().map(|(&n, &c)| (n, c)).collect();
+        counts.sort_by_key(|&(n, c)| (std::cmp::Reverse(c), n));
+        counts.truncate(k);
+        counts
+    }
+}
+
+// Returns the first generation from which the set of distinct values never
+// changes, looking at most `limit` generations ahead. Only the values are
+// tracked, so this never overflows.
+pub fn stabilization(stones: &[u64], rules: &Rules, limit: usize) -> Result<Option<usize>> {
+    let mut values: HashSet<u64> = stones.iter().copied().collect();
+    for generation in 0..limit {
+        let mut next = HashSet::with_capacity(values.len());
+        for &n in &values {
+            next.extend(rules.apply(n)?);
+        }
+        if next == values {
+            return Ok(Some(generation));
+        }
+        values = next;
+    }
+    Ok(None)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Count,
+    Distinct,
+    Top(usize),
+    Stabilize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub blinks: usize,
+    pub rules: Option<PathBuf>,
+    pub mode: Mode,
+}
+
+const STABILIZATION_LIMIT: usize = 10000;
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--blinks" => {
+                    self.blinks = args.next().context("Missing --blinks value")?.parse()?;
+                }
+                "--rules" => {
+                    let path = args.next().context("Missing --rules value")?;
+                    self.rules = Some(PathBuf::from(path));
+                }
+                "--distinct" => self.mode = Mode::Distinct,
+                "--top" => {
+                    let k = args.next().context("Missing --top value")?.parse()?;
+                    self.mode = Mode::Top(k);
+                }
+                "--stabilize" => self.mode = Mode::Stabilize,
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+}
+
+impl Options {
+    pub fn load_rules(&self) -> Result<Rules> {
+        match &self.rules {
+            Some(path) => std::fs::read_to_string(path)?.parse(),
+            None => Ok(Rules::standard()),
+        }
+    }
+}
+
+fn blink_all(problem: &Problem, rules: &Rules, blinks: usize) -> Result<Stones> {
+    let mut stones = Stones::new(&problem.stones);
+    for _ in 0..blinks {
+        stones.blink(rules)?;
+    }
+    Ok(stones)
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<u128> {
+    let rules = options.load_rules()?;
+    blink_all(problem, &rules, options.blinks)?.total()
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let rules = options.load_rules()?;
+    let mut output = String::new();
+    match options.mode {
+        Mode::Count => {
+            let stones = blink_all(problem, &rules, options.blinks)?;
+            writeln!(output, "{}", stones.total()?)?;
+        }
+        Mode::Distinct => {
+            let stones = blink_all(problem, &rules, options.blinks)?;
+            writeln!(output, "{}", stones.distinct())?;
+        }
+        Mode::Top(k) => {
+            let stones = blink_all(problem, &rules, options.blinks)?;
+            for (n, count) in stones.top(k) {
+                writeln!(output, "{} {}", n, count)?;
+            }
+        }
+        Mode::Stabilize => {
+            let generation = stabilization(&problem.stones, &rules, STABILIZATION_LIMIT)?
+                .with_context(|| format!("Not stable after {} blinks", STABILIZATION_LIMIT))?;
+            writeln!(output, "{}", generation)?;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stones() -> Result<()> {
+        let rules = Rules::standard();
+        let mut stones = Stones::new(&[125, 17]);
+        for _ in 0..6 {
+            stones.blink(&rules)?;
+        }
+        // 2097446912 14168 4048 2 0 2 4 40 48 2024 40 48 80 96 2 8 6 7 6 0 3 2
+        assert_eq!(stones.total()?, 22);
+        assert_eq!(stones.distinct(), 15);
+        assert_eq!(stones.top(3), [(2, 4), (0, 2), (6, 2)]);
+        Ok(())
+    }
+
+    #[test]
+    fn big_counts() -> Result<()> {
+        let rules = Rules::standard();
+        let mut stones = Stones::new(&[125, 17]);
+        for _ in 0..120 {
+            stones.blink(&rules)?;
+        }
+        assert!(stones.total()? > u64::MAX as u128);
+        while stones.blink(&rules).is_ok() {}
+        assert!(stones.generation > 150);
+        Ok(())
+    }
+
+    #[test]
+    fn value_overflow() {
+        // 19 digits, so the stone is multiplied by 2024 and overflows.
+        let rules = Rules::standard();
+        let mut stones = Stones::new(&[1_000_000_000_000_000_000]);
+        assert!(stones.blink(&rules).is_err());
+        assert!(stabilization(&[1_000_000_000_000_000_000], &rules, 10).is_err());
+    }
+
+    #[test]
+    fn stable() -> Result<()> {
+        let rules = Rules::standard();
+        assert_eq!(
+            stabilization(&[0], &rules, 100)?,
+            stabilization(&[1], &rules, 100)?.map(|g| g + 1)
+        );
+        let generation = stabilization(&[125, 17], &rules, 1000)?.unwrap();
+        let mut values: HashSet<u64> = [125, 17].into();
+        for _ in 0..generation {
+            values = values
+                .iter()
+                .flat_map(|&n| rules.apply(n).unwrap())
+                .collect();
+        }
+        assert_eq!(values.len(), 54);
+        Ok(())
+    }
+
+    #[test]
+    fn other_base() -> Result<()> {
+        let rules: Rules = "eq 0 -> set 1\neven-digits 2 -> split 2\nany -> mul 3".parse()?;
+        // 1 -> 3 (0b11) -> 1 1 -> 3 3 -> 1 1 1 1
+        let mut stones = Stones::new(&[1]);
+        for _ in 0..4 {
+            stones.blink(&rules)?;
+        }
+        assert_eq!(stones.total()?, 4);
+        assert_eq!(stones.top(1), [(1, 4)]);
+        Ok(())
+    }
+}
@@ -0,0 +1,239 @@
+use std::str::FromStr;
+
+use anyhow::{bail, ensure, Context, Error, Result};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Predicate {
+    Any,
+    Equals(u64),
+    DivisibleBy(u64),
+    EvenDigits { base: u64 },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Transform {
+    Set(u64),
+    Add(u64),
+    Multiply(u64),
+    // Splits the digits into a left and right half; the right half gets the
+    // smaller share of an odd digit count.
+    Split { base: u64 },
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Rule {
+    pub predicate: Predicate,
+    pub transform: Transform,
+}
+
+fn digits(n: u64, base: u64) -> u32 {
+    n.checked_ilog(base).unwrap_or(0) + 1
+}
+
+impl Predicate {
+    pub fn matches(self, n: u64) -> bool {
+        match self {
+            Predicate::Any => true,
+            Predicate::Equals(m) => n == m,
+            Predicate::DivisibleBy(m) => n.is_multiple_of(m),
+            Predicate::EvenDigits { base } => digits(n, base).is_multiple_of(2),
+        }
+    }
+}
+
+impl Transform {
+    pub fn apply(self, n: u64) -> Result<Vec<u64>> {
+        Ok(match self {
+            Transform::Set(m) => vec![m],
+            Transform::Add(m) => vec![n.checked_add(m).context("Stone value overflows")?],
+            Transform::Multiply(m) => vec![n.checked_mul(m).context("Stone value overflows")?],
+            Transform::Split { base } => {
+                let half = base.pow(digits(n, base) / 2);
+                vec![n / half, n % half]
+            }
+        })
+    }
+}
+
+// Rules are tried in order and the first matching one applies. Stones that
+// match no rule stay as they are.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Rules {
+    rules: Vec<Rule>,
+}
+
+impl Rules {
+    pub fn builder() -> RulesBuilder {
+        RulesBuilder::default()
+    }
+
+    // The rules from the puzzle.
+    pub fn standard() -> Rules {
+        Rules::builder()
+            .when(Predicate::Equals(0), Transform::Set(1))
+            .when(
+                Predicate::EvenDigits { base: 10 },
+                Transform::Split { base: 10 },
+            )
+            .otherwise(Transform::Multiply(2024))
+            .build()
+            .unwrap()
+    }
+
+    pub fn rules(&self) -> &[Rule] {
+        &self.rules
+    }
+
+    pub fn apply(&self, n: u64) -> Result<Vec<u64>> {
+        match self.rules.iter().find(|rule| rule.predicate.matches(n)) {
+            Some(rule) => rule.transform.apply(n),
+            None => Ok(vec![n]),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RulesBuilder {
+    rules: Vec<Rule>,
+}
+
+impl RulesBuilder {
+    pub fn when(mut self, predicate: Predicate, transform: Transform) -> Self {
+        self.rules.push(Rule {
+            predicate,
+            transform,
+        });
+        self
+    }
+
+    pub fn otherwise(self, transform: Transform) -> Self {
+        self.when(Predicate::Any, transform)
+    }
+
+    pub fn build(self) -> Result<Rules> {
+        for rule in &self.rules {
+            match rule.predicate {
+                Predicate::DivisibleBy(0) => bail!("Cannot test divisibility by 0"),
+                Predicate::EvenDigits { base } => ensure!(base >= 2, "Invalid base {}", base),
+                _ => {}
+            }
+            if let Transform::Split { base } = rule.transform {
+                ensure!(base >= 2, "Invalid base {}", base);
+            }
+        }
+        Ok(Rules { rules: self.rules })
+    }
+}
+
+fn parse_number(words: &[&str], default: Option<u64>) -> Result<u64> {
+    match (words, default) {
+        ([], Some(default)) => Ok(default),
+        ([word], _) => Ok(word.parse()?),
+        _ => bail!("Expected a number: {}", words.join(" ")),
+    }
+}
+
+impl FromStr for Predicate {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let words: Vec<&str> = s.split_ascii_whitespace().collect();
+        let (&name, args) = words.split_first().context("Missing predicate")?;
+        Ok(match name {
+            "any" if args.is_empty() => Predicate::Any,
+            "eq" => Predicate::Equals(parse_number(args, None)?),
+            "div" => Predicate::DivisibleBy(parse_number(args, None)?),
+            "even-digits" => Predicate::EvenDigits {
+                base: parse_number(args, Some(10))?,
+            },
+            _ => bail!("Unknown predicate: {}", s),
+        })
+    }
+}
+
+impl FromStr for Transform {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let words: Vec<&str> = s.split_ascii_whitespace().collect();
+        let (&name, args) = words.split_first().context("Missing transform")?;
+        Ok(match name {
+            "set" => Transform::Set(parse_number(args, None)?),
+            "add" => Transform::Add(parse_number(args, None)?),
+            "mul" => Transform::Multiply(parse_number(args, None)?),
+            "split" => Transform::Split {
+                base: parse_number(args, Some(10))?,
+            },
+            _ => bail!("Unknown transform: {}", s),
+        })
+    }
+}
+
+// One rule per line, e.g.:
+//
+//   # The puzzle rules
+//   eq 0 -> set 1
+//   even-digits 10 -> split 10
+//   any -> mul 2024
+impl FromStr for Rules {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut builder = Rules::builder();
+        for line in input.lines() {
+            let line = line.split_once('#').map_or(line, |(rule, _)| rule).trim();
+            if line.is_empty() {
+                continue;
+            }
+            let (predicate, transform) = line
+                .split_once("->")
+                .with_context(|| format!("Bad rule: {}", line))?;
+            builder = builder.when(predicate.parse()?, transform.parse()?);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() -> Result<()> {
+        let rules: Rules = "# The puzzle rules
+eq 0 -> set 1
+even-digits -> split   # base 10
+any -> mul 2024
+"
+        .parse()?;
+        assert_eq!(rules, Rules::standard());
+        assert!("eq 0 => set 1".parse::<Rules>().is_err());
+        assert!("even-digits 1 -> split".parse::<Rules>().is_err());
+        assert!("any 3 -> set 1".parse::<Rules>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn apply() -> Result<()> {
+        let rules = Rules::standard();
+        assert_eq!(rules.apply(0)?, [1]);
+        assert_eq!(rules.apply(1000)?, [10, 0]);
+        assert_eq!(rules.apply(999)?, [999 * 2024]);
+
+        // 0b1011 -> 0b10, 0b11
+        let binary = Rules::builder()
+            .when(
+                Predicate::EvenDigits { base: 2 },
+                Transform::Split { base: 2 },
+            )
+            .when(Predicate::DivisibleBy(3), Transform::Add(1))
+            .build()?;
+        assert_eq!(binary.apply(11)?, [2, 3]);
+        assert_eq!(binary.apply(21)?, [22]);
+        assert_eq!(binary.apply(7)?, [7]);
+
+        let overflow = Rules::builder().otherwise(Transform::Multiply(2)).build()?;
+        assert!(overflow.apply(u64::MAX).is_err());
+        Ok(())
+    }
+}
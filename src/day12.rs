@@ -0,0 +1,255 @@
+use std::{fmt::Write, ops::Add, str::FromStr};
+
+use anyhow::{bail, ensure, Error, Result};
+
+pub mod geometry;
+pub mod labels;
+
+use geometry::Outline;
+use labels::Labels;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Position {
+    pub i: i32,
+    pub j: i32,
+}
+
+impl Position {
+    pub const ADJACENTS: [Position; 4] = [
+        Position { i: -1, j: 0 },
+        Position { i: 0, j: 1 },
+        Position { i: 1, j: 0 },
+        Position { i: 0, j: -1 },
+    ];
+
+    pub fn rotate_clockwise(self) -> Position {
+        Position {
+            i: self.j,
+            j: -self.i,
+        }
+    }
+
+    pub fn rotate_counterclockwise(self) -> Position {
+        Position {
+            i: -self.j,
+            j: self.i,
+        }
+    }
+}
+
+impl Add<Position> for Position {
+    type Output = Position;
+
+    fn add(self, other: Position) -> Position {
+        Position {
+            i: self.i + other.i,
+            j: self.j + other.j,
+        }
+    }
+}
+
+// Plants in row-major order.
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub plants: Vec<char>,
+    pub height: i32,
+    pub width: i32,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let lines: Vec<&str> = input.lines().collect();
+        let width = lines.first().map_or(0, |line| line.chars().count());
+        ensure!(
+            lines.iter().all(|line| line.chars().count() == width),
+            "Rows must have the same length"
+        );
+        let plants = lines.iter().flat_map(|line| line.chars()).collect();
+        Ok(Problem {
+            plants,
+            height: lines.len() as i32,
+            width: width as i32,
+        })
+    }
+}
+
+impl Problem {
+    pub fn index(&self, pos: Position) -> usize {
+        (pos.i * self.width + pos.j) as usize
+    }
+
+    pub fn get(&self, pos: Position) -> Option<char> {
+        if pos.i < 0 || pos.j < 0 || pos.i >= self.height || pos.j >= self.width {
+            return None;
+        }
+        Some(self.plants[self.index(pos)])
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |i| (0..width).map(move |j| Position { i, j }))
+    }
+}
+
+// Cells are sorted in reading order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Region {
+    pub plant: char,
+    pub cells: Vec<Position>,
+}
+
+impl Region {
+    pub fn area(&self) -> usize {
+        self.cells.len()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Pricing {
+    Perimeter,
+    Sides,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Price,
+    Report,
+    Svg,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub pricing: Pricing,
+    pub mode: Mode,
+}
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        for arg in args {
+            match arg.as_str() {
+                "--report" => self.mode = Mode::Report,
+                "--svg" => self.mode = Mode::Svg,
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+}
+
+fn trace_outlines(regions: &[Region]) -> Result<Vec<Outline>> {
+    regions.iter().map(Outline::new).collect()
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<usize> {
+    let stats = Labels::new(problem).stats(problem);
+    let price = stats
+        .iter()
+        .map(|region| match options.pricing {
+            Pricing::Perimeter => region.area * region.perimeter,
+            Pricing::Sides => region.area * region.sides,
+        })
+        .sum();
+    Ok(price)
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let mut output = String::new();
+    match options.mode {
+        Mode::Price => writeln!(output, "{}", solve(problem, options)?)?,
+        Mode::Report => {
+            let labels = Labels::new(problem);
+            let outlines = trace_outlines(&labels.regions(problem))?;
+            let enclosures = labels.enclosures(&outlines);
+            let stats = labels.stats(problem);
+            for (id, (region, outline)) in stats.iter().zip(&outlines).enumerate() {
+                write!(
+                    output,
+                    "Region {} ({} at {},{}): area {}, perimeter {}, sides {}, holes {}",
+                    id,
+                    region.plant,
+                    region.first.i,
+                    region.first.j,
+                    region.area,
+                    region.perimeter,
+                    region.sides,
+                    outline.holes.len()
+                )?;
+                let inside: Vec<String> = enclosures
+                    .iter()
+                    .filter(|&&(inner, _)| inner == id)
+                    .map(|(_, outer)| outer.to_string())
+                    .collect();
+                if !inside.is_empty() {
+                    write!(output, ", inside {}", inside.join(" "))?;
+                }
+                writeln!(output)?;
+            }
+        }
+        Mode::Svg => {
+            let regions = Labels::new(problem).regions(problem);
+            let outlines = trace_outlines(&regions)?;
+            output = geometry::to_svg(problem, &regions, &outlines)?;
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn enclosures() -> Result<()> {
+        let problem: Problem = "AAAAA
+ABBBA
+ABCBA
+ABBBA
+AAAAA
+DDDDD
+"
+        .parse()?;
+        let labels = Labels::new(&problem);
+        let regions = labels.regions(&problem);
+        let outlines = trace_outlines(&regions)?;
+        assert_eq!(regions.iter().map(|r| r.plant).collect::<String>(), "ABCD");
+        assert_eq!(labels.enclosures(&outlines), [(1, 0), (2, 0), (2, 1)]);
+
+        // A diagonal gap in the ring is not a hole.
+        let problem: Problem = "AAAB
+ABAB
+AABB
+"
+        .parse()?;
+        let labels = Labels::new(&problem);
+        let outlines = trace_outlines(&labels.regions(&problem))?;
+        assert!(outlines[0].holes.is_empty());
+        assert_eq!(labels.enclosures(&outlines), []);
+        Ok(())
+    }
+
+    #[test]
+    fn outlines_match_stats() -> Result<()> {
+        let problem: Problem = "RRRRIICCFF
+RRRRIICCCF
+VVRRRCCFFF
+VVRCCCJFFF
+VVVVCJJCFE
+VVIVCCJJEE
+VVIIICJJEE
+MIIIIIJJEE
+MIIISIJEEE
+MMMISSJEEE
+"
+        .parse()?;
+        let labels = Labels::new(&problem);
+        let outlines = trace_outlines(&labels.regions(&problem))?;
+        for (region, outline) in labels.stats(&problem).iter().zip(&outlines) {
+            assert_eq!(region.area, outline.area());
+            assert_eq!(region.perimeter, outline.perimeter());
+            assert_eq!(region.sides, outline.sides());
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,248 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Write,
+};
+
+use anyhow::{ensure, Context, Result};
+
+use super::{Position, Problem, Region};
+
+// Corners are lattice points: cell (i, j) spans corners (i, j) to
+// (i + 1, j + 1).
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Polygon {
+    pub corners: Vec<Position>,
+}
+
+impl Polygon {
+    pub fn sides(&self) -> usize {
+        self.corners.len()
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.edges()
+            .map(|(a, b)| ((a.i - b.i).abs() + (a.j - b.j).abs()) as usize)
+            .sum()
+    }
+
+    // Positive for outer boundaries, negative for holes.
+    pub fn signed_area(&self) -> i64 {
+        let twice: i64 = self
+            .edges()
+            .map(|(a, b)| a.j as i64 * b.i as i64 - b.j as i64 * a.i as i64)
+            .sum();
+        twice / 2
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (Position, Position)> + '_ {
+        self.corners
+            .iter()
+            .copied()
+            .zip(self.corners.iter().copied().cycle().skip(1))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Outline {
+    pub outer: Polygon,
+    pub holes: Vec<Polygon>,
+}
+
+// Corner where the edge on each side of a cell starts, in ADJACENTS order.
+// Edges run clockwise around the region, i.e. with the region on the right.
+const EDGE_STARTS: [Position; 4] = [
+    Position { i: 0, j: 0 },
+    Position { i: 0, j: 1 },
+    Position { i: 1, j: 1 },
+    Position { i: 1, j: 0 },
+];
+
+// Follows boundary edges into closed loops. Where two loops touch at a
+// corner, the walk turns right so that the loops hug the region and cells
+// touching only diagonally stay apart.
+fn trace(cells: &HashSet<Position>) -> Result<Vec<Polygon>> {
+    let mut outgoing: HashMap<Position, Vec<Position>> = HashMap::new();
+    for &cell in cells {
+        for (dir, start) in Position::ADJACENTS.into_iter().zip(EDGE_STARTS) {
+            if !cells.contains(&(cell + dir)) {
+                outgoing
+                    .entry(cell + start)
+                    .or_default()
+                    .push(dir.rotate_clockwise());
+            }
+        }
+    }
+
+    let mut starts: Vec<Position> = outgoing.keys().copied().collect();
+    starts.sort();
+    let mut polygons = Vec::new();
+    for start in starts {
+        while let Some(first) = outgoing.get_mut(&start).and_then(|dirs| dirs.pop()) {
+            let mut corners = Vec::new();
+            let (mut pos, mut heading) = (start, first);
+            loop {
+                pos = pos + heading;
+                let closing = pos == start;
+                let dirs = outgoing.entry(pos).or_default();
+                let next = [
+                    heading.rotate_clockwise(),
+                    heading,
+                    heading.rotate_counterclockwise(),
+                ]
+                .into_iter()
+                .find(|dir| dirs.contains(dir) || (closing && *dir == first))
+                .context("Boundary is not closed")?;
+                if next != heading {
+                    corners.push(pos);
+                }
+                if closing && next == first {
+                    break;
+                }
+                dirs.retain(|dir| *dir != next);
+                heading = next;
+            }
+            // Start from the corner the loop was entered at.
+            corners.rotate_right(1);
+            polygons.push(Polygon { corners });
+        }
+    }
+    Ok(polygons)
+}
+
+impl Outline {
+    pub fn new(region: &Region) -> Result<Outline> {
+        let cells: HashSet<Position> = region.cells.iter().copied().collect();
+        let (outers, mut holes): (Vec<Polygon>, Vec<Polygon>) = trace(&cells)?
+            .into_iter()
+            .partition(|polygon| polygon.signed_area() > 0);
+        ensure!(outers.len() == 1, "Region is not connected");
+        holes.sort_by_key(|hole| hole.corners[0]);
+        Ok(Outline {
+            outer: outers.into_iter().next().unwrap(),
+            holes,
+        })
+    }
+
+    pub fn polygons(&self) -> impl Iterator<Item = &Polygon> {
+        std::iter::once(&self.outer).chain(&self.holes)
+    }
+
+    pub fn sides(&self) -> usize {
+        self.polygons().map(Polygon::sides).sum()
+    }
+
+    pub fn perimeter(&self) -> usize {
+        self.polygons().map(Polygon::perimeter).sum()
+    }
+
+    pub fn area(&self) -> usize {
+        self.polygons().map(Polygon::signed_area).sum::<i64>() as usize
+    }
+}
+
+const PIXELS_PER_CELL: i32 = 12;
+
+// Spreads hues so that neighbouring letters get distinct colours.
+fn plant_color(plant: char) -> String {
+    format!("hsl({}, 60%, 65%)", plant as u32 * 137 % 360)
+}
+
+// Draws each region as a path with its holes cut out, and marks every corner
+// so that side counts can be checked by eye.
+pub fn to_svg(problem: &Problem, regions: &[Region], outlines: &[Outline]) -> Result<String> {
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="-1 -1 {} {}">"#,
+        problem.width * PIXELS_PER_CELL + 2,
+        problem.height * PIXELS_PER_CELL + 2,
+        problem.width * PIXELS_PER_CELL + 2,
+        problem.height * PIXELS_PER_CELL + 2
+    )?;
+    for (region, outline) in regions.iter().zip(outlines) {
+        let mut path = String::new();
+        for polygon in outline.polygons() {
+            for (k, corner) in polygon.corners.iter().enumerate() {
+                write!(
+                    path,
+                    "{}{},{} ",
+                    if k == 0 { "M" } else { "L" },
+                    corner.j * PIXELS_PER_CELL,
+                    corner.i * PIXELS_PER_CELL
+                )?;
+            }
+            path.push_str("Z ");
+        }
+        writeln!(
+            svg,
+            r#"  <path d="{}" fill="{}" fill-rule="evenodd" stroke="black"><title>{}: area {}, sides {}</title></path>"#,
+            path.trim_end(),
+            plant_color(region.plant),
+            region.plant,
+            region.area(),
+            outline.sides()
+        )?;
+        for corner in outline.polygons().flat_map(|polygon| &polygon.corners) {
+            writeln!(
+                svg,
+                r#"  <circle cx="{}" cy="{}" r="1.5"/>"#,
+                corner.j * PIXELS_PER_CELL,
+                corner.i * PIXELS_PER_CELL
+            )?;
+        }
+    }
+    writeln!(svg, "</svg>")?;
+    Ok(svg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day12::labels::Labels;
+
+    fn outline(input: &str, index: usize) -> Result<Outline> {
+        let problem: Problem = input.parse()?;
+        Outline::new(&Labels::new(&problem).regions(&problem)[index])
+    }
+
+    #[test]
+    fn holes() -> Result<()> {
+        let outline = outline("OOOOO\nOXOXO\nOOOOO\nOXOXO\nOOOOO\n", 0)?;
+        assert_eq!(
+            outline.outer.corners,
+            [
+                Position { i: 0, j: 0 },
+                Position { i: 0, j: 5 },
+                Position { i: 5, j: 5 },
+                Position { i: 5, j: 0 },
+            ]
+        );
+        assert_eq!(outline.holes.len(), 4);
+        assert_eq!(
+            outline.holes[0].corners,
+            [
+                Position { i: 1, j: 1 },
+                Position { i: 2, j: 1 },
+                Position { i: 2, j: 2 },
+                Position { i: 1, j: 2 },
+            ]
+        );
+        assert_eq!(outline.area(), 21);
+        assert_eq!(outline.perimeter(), 36);
+        assert_eq!(outline.sides(), 20);
+        Ok(())
+    }
+
+    #[test]
+    fn pinch() -> Result<()> {
+        // The two B regions meet at a corner, which leaves a single hole in
+        // A with 8 sides.
+        let input = "AAAAAA\nAAABBA\nAAABBA\nABBAAA\nABBAAA\nAAAAAA\n";
+        let outline = outline(input, 0)?;
+        assert_eq!(outline.holes.len(), 1);
+        assert_eq!(outline.holes[0].sides(), 8);
+        assert_eq!(outline.sides(), 12);
+        assert_eq!(outline.area(), 28);
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use super::{geometry::Outline, Position, Problem, Region};
+
+#[derive(Clone, Copy, Debug)]
+enum UnionFindEntry {
+    Root { size: usize },
+    Child { parent: usize },
+}
+
+#[derive(Clone, Debug, Default)]
+struct UnionFind {
+    entries: Vec<UnionFindEntry>,
+}
+
+impl UnionFind {
+    pub fn add(&mut self) -> usize {
+        self.entries.push(UnionFindEntry::Root { size: 1 });
+        self.entries.len() - 1
+    }
+
+    pub fn find(&mut self, item: usize) -> (usize, usize) {
+        match self.entries[item] {
+            UnionFindEntry::Root { size } => (item, size),
+            UnionFindEntry::Child { parent } => {
+                let (root, size) = self.find(parent);
+                self.entries[item] = UnionFindEntry::Child { parent: root };
+                (root, size)
+            }
+        }
+    }
+
+    pub fn merge(&mut self, a: usize, b: usize) -> usize {
+        let (a_root, a_size) = self.find(a);
+        let (b_root, b_size) = self.find(b);
+        if a_root == b_root {
+            return a_root;
+        }
+        let (root, child) = if a_size < b_size {
+            (b_root, a_root)
+        } else {
+            (a_root, b_root)
+        };
+        self.entries[child] = UnionFindEntry::Child { parent: root };
+        self.entries[root] = UnionFindEntry::Root {
+            size: a_size + b_size,
+        };
+        root
+    }
+}
+
+// Region id of every cell, in row-major order. Ids are numbered by the
+// first cell of each region in reading order.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Labels {
+    pub height: i32,
+    pub width: i32,
+    pub ids: Vec<usize>,
+    pub count: usize,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct RegionStats {
+    pub plant: char,
+    pub first: Position,
+    pub area: usize,
+    pub perimeter: usize,
+    pub sides: usize,
+}
+
+impl Labels {
+    // Two-pass connected component labelling: the first pass gives each cell
+    // the label of its upper or left neighbour and records equivalences, the
+    // second resolves them.
+    pub fn new(problem: &Problem) -> Labels {
+        let mut sets = UnionFind::default();
+        let mut provisional: Vec<usize> = Vec::with_capacity(problem.plants.len());
+        for pos in problem.positions() {
+            let plant = problem.get(pos);
+            let up = Position {
+                i: pos.i - 1,
+                ..pos
+            };
+            let left = Position {
+                j: pos.j - 1,
+                ..pos
+            };
+            let up = (problem.get(up) == plant).then(|| provisional[problem.index(up)]);
+            let left = (problem.get(left) == plant).then(|| provisional[problem.index(left)]);
+            let label = match (up, left) {
+                (Some(up), Some(left)) => {
+                    sets.merge(up, left);
+                    up
+                }
+                (Some(label), None) | (None, Some(label)) => label,
+                (None, None) => sets.add(),
+            };
+            provisional.push(label);
+        }
+
+        let mut renumber = vec![usize::MAX; sets.entries.len()];
+        let mut count = 0;
+        let ids = provisional
+            .into_iter()
+            .map(|label| {
+                let (root, _) = sets.find(label);
+                if renumber[root] == usize::MAX {
+                    renumber[root] = count;
+                    count += 1;
+                }
+                renumber[root]
+            })
+            .collect();
+        Labels {
+            height: problem.height,
+            width: problem.width,
+            ids,
+            count,
+        }
+    }
+
+    pub fn get(&self, pos: Position) -> Option<usize> {
+        if pos.i < 0 || pos.j < 0 || pos.i >= self.height || pos.j >= self.width {
+            return None;
+        }
+        Some(self.ids[(pos.i * self.width + pos.j) as usize])
+    }
+
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        let width = self.width;
+        (0..self.height).flat_map(move |i| (0..width).map(move |j| Position { i, j }))
+    }
+
+    pub fn regions(&self, problem: &Problem) -> Vec<Region> {
+        let mut regions: Vec<Region> = Vec::with_capacity(self.count);
+        for (pos, &id) in self.positions().zip(&self.ids) {
+            if id == regions.len() {
+                regions.push(Region {
+                    plant: problem.get(pos).unwrap(),
+                    cells: Vec::new(),
+                });
+            }
+            regions[id].cells.push(pos);
+        }
+        regions
+    }
+
+    // Computes all region statistics in one scan. Each cell adds its missing
+    // neighbours to the perimeter, and each of its four corners where the
+    // boundary turns to the side count.
+    pub fn stats(&self, problem: &Problem) -> Vec<RegionStats> {
+        let mut stats: Vec<RegionStats> = Vec::with_capacity(self.count);
+        for (pos, &id) in self.positions().zip(&self.ids) {
+            if id == stats.len() {
+                stats.push(RegionStats {
+                    plant: problem.get(pos).unwrap(),
+                    first: pos,
+                    area: 0,
+                    perimeter: 0,
+                    sides: 0,
+                });
+            }
+            let same = |dir: Position| self.get(pos + dir) == Some(id);
+            let region = &mut stats[id];
+            region.area += 1;
+            for dir in Position::ADJACENTS {
+                let normal = dir.rotate_clockwise();
+                if !same(dir) {
+                    region.perimeter += 1;
+                }
+                let convex = !same(dir) && !same(normal);
+                let concave = same(dir) && same(normal) && !same(dir + normal);
+                if convex || concave {
+                    region.sides += 1;
+                }
+            }
+        }
+        stats
+    }
+
+    // Returns (inner, outer) pairs of region ids such that inner lies in a
+    // hole of outer, i.e. cannot reach the map edge without crossing it.
+    pub fn enclosures(&self, outlines: &[Outline]) -> Vec<(usize, usize)> {
+        let mut pairs = Vec::new();
+        for (outer, outline) in outlines.iter().enumerate() {
+            if outline.holes.is_empty() {
+                continue;
+            }
+            // Cells outside the region touching only diagonally are still
+            // connected, since the region boundary pinches there.
+            let mut outside = vec![false; self.ids.len()];
+            let mut stack: Vec<Position> = self
+                .positions()
+                .filter(|pos| {
+                    pos.i == 0 || pos.j == 0 || pos.i == self.height - 1 || pos.j == self.width - 1
+                })
+                .collect();
+            while let Some(current) = stack.pop() {
+                let index = (current.i * self.width + current.j) as usize;
+                if self.ids[index] == outer || outside[index] {
+                    continue;
+                }
+                outside[index] = true;
+                for di in -1..=1 {
+                    for dj in -1..=1 {
+                        let next = current + Position { i: di, j: dj };
+                        if self.get(next).is_some() {
+                            stack.push(next);
+                        }
+                    }
+                }
+            }
+            let mut inside = vec![false; self.count];
+            for (&id, &reached) in self.ids.iter().zip(&outside) {
+                if id != outer && !reached {
+                    inside[id] = true;
+                }
+            }
+            pairs.extend(
+                (0..self.count)
+                    .filter(|&id| inside[id])
+                    .map(|id| (id, outer)),
+            );
+        }
+        pairs.sort();
+        pairs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn labels() -> anyhow::Result<()> {
+        // The C region is U-shaped, so its two arms get different provisional
+        // labels that are merged on the last row.
+        let problem: Problem = "CACAB
+CCCBB
+"
+        .parse()?;
+        let labels = Labels::new(&problem);
+        assert_eq!(labels.ids, [0, 1, 0, 2, 3, 0, 0, 0, 3, 3]);
+        assert_eq!(labels.count, 4);
+
+        let stats = labels.stats(&problem);
+        assert_eq!(
+            stats[0],
+            RegionStats {
+                plant: 'C',
+                first: Position { i: 0, j: 0 },
+                area: 5,
+                perimeter: 12,
+                sides: 8,
+            }
+        );
+        assert_eq!(stats[3].sides, 6);
+        Ok(())
+    }
+}
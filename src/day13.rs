@@ -0,0 +1,771 @@
+use std::{fmt::Write, str::FromStr};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+use regex::Regex;
+
+// Upper bound on the press combinations tried when a machine has more than
+// one free button.
+pub const MAX_BRANCHES: usize = 1_000_000;
+const MAX_DOUBLINGS: u32 = 64;
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Button {
+    pub name: String,
+    pub delta: Vec<i64>,
+    pub cost: i64,
+    pub limit: Option<i64>,
+}
+
+fn default_cost(name: &str) -> Option<i64> {
+    match name {
+        "A" => Some(3),
+        "B" => Some(1),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Machine {
+    pub buttons: Vec<Button>,
+    pub prize: Vec<i64>,
+}
+
+// Splits "X+94, Y-3, Cost=2" into (name, value, is_assignment) triples.
+fn parse_items(text: &str) -> Result<Vec<(String, i64, bool)>> {
+    let pattern = Regex::new(r"^([A-Za-z]+)(?:([+-])([0-9]+)|=(-?[0-9]+))$")?;
+    text.split(", ")
+        .map(|item| {
+            let c = pattern
+                .captures(item)
+                .with_context(|| format!("Bad item: {}", item))?;
+            let name = c.get(1).unwrap().as_str().to_owned();
+            Ok(match (c.get(2), c.get(3), c.get(4)) {
+                (Some(sign), Some(value), _) => {
+                    let value: i64 = value.as_str().parse()?;
+                    let value = if sign.as_str() == "-" { -value } else { value };
+                    (name, value, false)
+                }
+                (_, _, Some(value)) => (name, value.as_str().parse()?, true),
+                _ => unreachable!(),
+            })
+        })
+        .collect()
+}
+
+impl FromStr for Machine {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let pattern = Regex::new(r"^(?:Button ([A-Za-z0-9]+)|Prize): (.*)$")?;
+        let mut buttons = Vec::new();
+        let mut prize = None;
+        for line in input.lines() {
+            let c = pattern
+                .captures(line.trim())
+                .with_context(|| format!("Bad line: {}", line))?;
+            let items = parse_items(c.get(2).unwrap().as_str())?;
+            match c.get(1) {
+                Some(name) => buttons.push((name.as_str().to_owned(), items)),
+                None => {
+                    ensure!(prize.is_none(), "Multiple prizes");
+                    ensure!(
+                        items.iter().all(|&(_, _, assign)| assign),
+                        "Prize must be given as X=..."
+                    );
+                    prize = Some(items);
+                }
+            }
+        }
+        let prize = prize.context("Missing prize")?;
+        ensure!(!buttons.is_empty(), "Missing buttons");
+
+        let axes: Vec<String> = prize.iter().map(|(axis, _, _)| axis.clone()).collect();
+        for (i, axis) in axes.iter().enumerate() {
+            ensure!(!axes[..i].contains(axis), "Duplicated axis {}", axis);
+        }
+        let buttons = buttons
+            .into_iter()
+            .map(|(name, items)| {
+                let mut delta = vec![0; axes.len()];
+                let mut seen = vec![false; axes.len()];
+                let mut cost = default_cost(&name);
+                let mut limit = None;
+                for (key, value, assign) in items {
+                    match (key.as_str(), assign) {
+                        ("Cost", true) => cost = Some(value),
+                        ("Limit", true) => {
+                            ensure!(value >= 0, "Negative limit for button {}", name);
+                            limit = Some(value);
+                        }
+                        (_, false) => {
+                            let axis = axes
+                                .iter()
+                                .position(|axis| *axis == key)
+                                .with_context(|| format!("Unknown axis {}", key))?;
+                            ensure!(!seen[axis], "Duplicated axis {} in button {}", key, name);
+                            seen[axis] = true;
+                            delta[axis] = value;
+                        }
+                        _ => bail!("Unknown property {} for button {}", key, name),
+                    }
+                }
+                let cost = cost.with_context(|| format!("Missing cost for button {}", name))?;
+                Ok(Button {
+                    name,
+                    delta,
+                    cost,
+                    limit,
+                })
+            })
+            .collect::<Result<_>>()?;
+        let prize = prize.into_iter().map(|(_, value, _)| value).collect();
+        Ok(Machine { buttons, prize })
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Outcome {
+    Win { presses: Vec<i128>, tokens: i128 },
+    NoSolution,
+    // Some button has a negative cost and can be pressed forever.
+    Unbounded,
+}
+
+impl Outcome {
+    pub fn tokens(&self) -> Option<i128> {
+        match self {
+            Outcome::Win { tokens, .. } => Some(*tokens),
+            _ => None,
+        }
+    }
+}
+
+fn sub_column(columns: &mut [Vec<i128>], target: usize, source: usize, q: i128) {
+    let source = columns[source].clone();
+    for (t, s) in columns[target].iter_mut().zip(source) {
+        *t -= q * s;
+    }
+}
+
+// Finds one integer solution of sum(presses[i] * delta[i]) = prize and a
+// basis of the press changes that keep the claw in place, by reducing the
+// button columns to echelon form with unimodular column operations.
+fn integer_solutions(machine: &Machine) -> Option<(Vec<i128>, Vec<Vec<i128>>)> {
+    let n = machine.buttons.len();
+    let mut columns: Vec<Vec<i128>> = machine
+        .buttons
+        .iter()
+        .map(|button| button.delta.iter().map(|&d| d as i128).collect())
+        .collect();
+    let mut transform: Vec<Vec<i128>> = (0..n)
+        .map(|i| (0..n).map(|j| (i == j) as i128).collect())
+        .collect();
+
+    // pivots[k] is the row whose leading entry is in column k.
+    let mut pivots = Vec::new();
+    for row in 0..machine.prize.len() {
+        let rank = pivots.len();
+        if rank == n {
+            break;
+        }
+        for col in rank + 1..n {
+            while columns[col][row] != 0 {
+                let q = columns[rank][row] / columns[col][row];
+                sub_column(&mut columns, rank, col, q);
+                sub_column(&mut transform, rank, col, q);
+                columns.swap(rank, col);
+                transform.swap(rank, col);
+            }
+        }
+        if columns[rank][row] != 0 {
+            pivots.push(row);
+        }
+    }
+
+    let mut coords: Vec<i128> = Vec::new();
+    for (row, &target) in machine.prize.iter().enumerate() {
+        let rest = target as i128
+            - coords
+                .iter()
+                .enumerate()
+                .map(|(k, &y)| columns[k][row] * y)
+                .sum::<i128>();
+        if pivots.get(coords.len()) == Some(&row) {
+            let p = columns[coords.len()][row];
+            if rest % p != 0 {
+                return None;
+            }
+            coords.push(rest / p);
+        } else if rest != 0 {
+            return None;
+        }
+    }
+
+    let rank = coords.len();
+    let base = (0..n)
+        .map(|i| (0..rank).map(|k| transform[k][i] * coords[k]).sum())
+        .collect();
+    Some((base, transform.split_off(rank)))
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+// coefs . k <= bound
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct Constraint {
+    coefs: Vec<i128>,
+    bound: i128,
+}
+
+impl Constraint {
+    // Divides by the common factor of the coefficients. Rounding the bound
+    // down is exact for integer points.
+    fn new(mut coefs: Vec<i128>, mut bound: i128) -> Self {
+        let g = coefs.iter().fold(0, |g, &c| gcd(g, c));
+        if g > 1 {
+            coefs.iter_mut().for_each(|c| *c /= g);
+            bound = bound.div_euclid(g);
+        }
+        Constraint { coefs, bound }
+    }
+
+    fn fix_first(&self, value: i128) -> Self {
+        Constraint::new(self.coefs[1..].to_vec(), self.bound - self.coefs[0] * value)
+    }
+}
+
+// Fourier-Motzkin elimination of one variable.
+fn eliminate(constraints: &[Constraint], var: usize) -> Vec<Constraint> {
+    let (mut result, mut pos, mut neg) = (Vec::new(), Vec::new(), Vec::new());
+    for c in constraints {
+        match c.coefs[var].signum() {
+            1 => pos.push(c),
+            -1 => neg.push(c),
+            _ => result.push(c.clone()),
+        }
+    }
+    for p in &pos {
+        for n in &neg {
+            let (a, b) = (p.coefs[var], -n.coefs[var]);
+            let coefs = p
+                .coefs
+                .iter()
+                .zip(&n.coefs)
+                .map(|(&x, &y)| x * b + y * a)
+                .collect();
+            result.push(Constraint::new(coefs, p.bound * b + n.bound * a));
+        }
+    }
+    result.sort();
+    result.dedup();
+    result
+}
+
+// Range of the first variable over the (rational) feasible region, or None
+// if it is empty. Missing ends are unbounded.
+fn first_range(constraints: &[Constraint], vars: usize) -> Option<(Option<i128>, Option<i128>)> {
+    let mut constraints = constraints.to_vec();
+    for var in 1..vars {
+        constraints = eliminate(&constraints, var);
+    }
+    let (mut lo, mut hi): (Option<i128>, Option<i128>) = (None, None);
+    for c in &constraints {
+        let a = c.coefs[0];
+        if a > 0 {
+            let bound = c.bound.div_euclid(a);
+            hi = Some(hi.map_or(bound, |hi| hi.min(bound)));
+        } else if a < 0 {
+            lo = lo.max(Some(-c.bound.div_euclid(-a)));
+        } else if c.bound < 0 {
+            return None;
+        }
+    }
+    match (lo, hi) {
+        (Some(lo), Some(hi)) if lo > hi => None,
+        _ => Some((lo, hi)),
+    }
+}
+
+enum Search {
+    Found(i128, Vec<i128>),
+    Infeasible,
+    Unbounded,
+    // Some enumerated variable has no finite range.
+    Open,
+}
+
+// Minimizes weights . k over integer points satisfying the constraints.
+// The last variable is chosen directly since the objective is linear; the
+// others are enumerated.
+fn search(constraints: &[Constraint], weights: &[i128], branches: &mut usize) -> Result<Search> {
+    let Some((&w, rest)) = weights.split_first() else {
+        return Ok(if constraints.iter().all(|c| c.bound >= 0) {
+            Search::Found(0, Vec::new())
+        } else {
+            Search::Infeasible
+        });
+    };
+    let Some((lo, hi)) = first_range(constraints, weights.len()) else {
+        return Ok(Search::Infeasible);
+    };
+
+    if rest.is_empty() {
+        let k = match (w.signum(), lo, hi) {
+            (1, Some(lo), _) => lo,
+            (-1, _, Some(hi)) => hi,
+            (0, lo, hi) => lo.or(hi).unwrap_or(0),
+            _ => return Ok(Search::Unbounded),
+        };
+        return Ok(Search::Found(w * k, vec![k]));
+    }
+
+    let (Some(lo), Some(hi)) = (lo, hi) else {
+        return Ok(Search::Open);
+    };
+    let mut best: Option<(i128, Vec<i128>)> = None;
+    for k in lo..=hi {
+        *branches += 1;
+        ensure!(
+            *branches <= MAX_BRANCHES,
+            "Gave up after exploring {} branches",
+            MAX_BRANCHES
+        );
+        let fixed: Vec<Constraint> = constraints.iter().map(|c| c.fix_first(k)).collect();
+        match search(&fixed, rest, branches)? {
+            Search::Found(cost, ks) => {
+                let cost = cost + w * k;
+                if best.as_ref().is_none_or(|(best, _)| cost < *best) {
+                    best = Some((cost, [k].into_iter().chain(ks).collect()));
+                }
+            }
+            Search::Infeasible => {}
+            result => return Ok(result),
+        }
+    }
+    Ok(best.map_or(Search::Infeasible, |(cost, ks)| Search::Found(cost, ks)))
+}
+
+// Like search, but when some variable is unbounded the objective itself is
+// capped, starting at its rational minimum and doubling the slack until an
+// integer point fits. With positive costs the cap bounds every press count.
+fn minimize(constraints: &[Constraint], weights: &[i128]) -> Result<Search> {
+    let mut branches = 0;
+    match search(constraints, weights, &mut branches)? {
+        Search::Open => {}
+        result => return Ok(result),
+    }
+
+    // z = weights . k, added as a new first variable.
+    let mut with_objective: Vec<Constraint> = constraints
+        .iter()
+        .map(|c| Constraint::new([0].into_iter().chain(c.coefs.clone()).collect(), c.bound))
+        .collect();
+    with_objective.push(Constraint::new(
+        [-1].into_iter().chain(weights.iter().copied()).collect(),
+        0,
+    ));
+    with_objective.push(Constraint::new(
+        [1].into_iter().chain(weights.iter().map(|w| -w)).collect(),
+        0,
+    ));
+    let Some((lo, _)) = first_range(&with_objective, weights.len() + 1) else {
+        return Ok(Search::Infeasible);
+    };
+    let Some(lo) = lo else {
+        return Ok(Search::Unbounded);
+    };
+
+    for slack in (0..MAX_DOUBLINGS).map(|i| (1i128 << i) - 1) {
+        let mut capped = constraints.to_vec();
+        capped.push(Constraint::new(weights.to_vec(), lo + slack));
+        match search(&capped, weights, &mut branches)? {
+            Search::Infeasible => {}
+            // Free presses with zero cost are still unbounded.
+            Search::Open => bail!("Too many ways to press the buttons; add press limits"),
+            result => return Ok(result),
+        }
+    }
+    bail!(
+        "No way to press the buttons within {} extra tokens",
+        (1i128 << MAX_DOUBLINGS) - 1
+    )
+}
+
+impl Button {
+    // Whether pressing the button leaves the claw where it is.
+    fn is_idle(&self) -> bool {
+        self.delta.iter().all(|&d| d == 0)
+    }
+
+    fn press_limit(&self, limit: Option<i64>) -> Option<i64> {
+        match (self.limit, limit) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+}
+
+impl Machine {
+    // Idle buttons only change the cost, so they are pressed as often as
+    // allowed if that pays and not at all otherwise. Left in, they would
+    // make the search unbounded even when no press counts work.
+    fn solve_without_idle(&self, limit: Option<i64>) -> Result<Outcome> {
+        let active = Machine {
+            buttons: self
+                .buttons
+                .iter()
+                .filter(|button| !button.is_idle())
+                .cloned()
+                .collect(),
+            prize: self.prize.clone(),
+        };
+        let (presses, mut tokens) = match active.solve(limit)? {
+            Outcome::Win { presses, tokens } => (presses, tokens),
+            outcome => return Ok(outcome),
+        };
+        let mut active_presses = presses.into_iter();
+        let mut presses = Vec::new();
+        for button in &self.buttons {
+            if !button.is_idle() {
+                presses.extend(active_presses.next());
+                continue;
+            }
+            let count = match (button.cost < 0, button.press_limit(limit)) {
+                (_, Some(limit)) if limit < 0 => return Ok(Outcome::NoSolution),
+                (true, Some(limit)) => limit as i128,
+                (true, None) => return Ok(Outcome::Unbounded),
+                (false, _) => 0,
+            };
+            tokens += count * button.cost as i128;
+            presses.push(count);
+        }
+        Ok(Outcome::Win { presses, tokens })
+    }
+
+    pub fn shifted(&self, offset: i64) -> Machine {
+        Machine {
+            buttons: self.buttons.clone(),
+            prize: self.prize.iter().map(|&p| p + offset).collect(),
+        }
+    }
+
+    // Finds the cheapest press counts reaching the prize. `limit` caps every
+    // button on top of its own limit.
+    pub fn solve(&self, limit: Option<i64>) -> Result<Outcome> {
+        if self.buttons.iter().any(Button::is_idle) {
+            return self.solve_without_idle(limit);
+        }
+        let Some((base, kernel)) = integer_solutions(self) else {
+            return Ok(Outcome::NoSolution);
+        };
+
+        // presses[i] = base[i] + sum(kernel[j][i] * k[j])
+        let mut constraints = Vec::new();
+        for (i, button) in self.buttons.iter().enumerate() {
+            let coefs: Vec<i128> = kernel.iter().map(|column| column[i]).collect();
+            let negated = coefs.iter().map(|c| -c).collect();
+            constraints.push(Constraint::new(negated, base[i]));
+            if let Some(limit) = button.press_limit(limit) {
+                constraints.push(Constraint::new(coefs, limit as i128 - base[i]));
+            }
+        }
+        let weights: Vec<i128> = kernel
+            .iter()
+            .map(|column| {
+                column
+                    .iter()
+                    .zip(&self.buttons)
+                    .map(|(&c, button)| c * button.cost as i128)
+                    .sum()
+            })
+            .collect();
+
+        match minimize(&constraints, &weights)? {
+            Search::Found(_, ks) => {
+                let presses: Vec<i128> = (0..self.buttons.len())
+                    .map(|i| base[i] + kernel.iter().zip(&ks).map(|(c, k)| c[i] * k).sum::<i128>())
+                    .collect();
+                let tokens = presses
+                    .iter()
+                    .zip(&self.buttons)
+                    .map(|(&p, button)| p * button.cost as i128)
+                    .sum();
+                Ok(Outcome::Win { presses, tokens })
+            }
+            Search::Infeasible => Ok(Outcome::NoSolution),
+            Search::Unbounded => Ok(Outcome::Unbounded),
+            Search::Open => unreachable!(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub machines: Vec<Machine>,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let machines = input
+            .trim()
+            .split("\n\n")
+            .map(|s| s.parse::<Machine>())
+            .collect::<Result<_>>()?;
+        Ok(Problem { machines })
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Answer,
+    Presses,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub offset: i64,
+    pub limit: Option<i64>,
+    pub mode: Mode,
+}
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--offset" => {
+                    self.offset = args.next().context("Missing --offset value")?.parse()?;
+                }
+                "--limit" => {
+                    self.limit = Some(args.next().context("Missing --limit value")?.parse()?);
+                }
+                "--no-limit" => self.limit = None,
+                "--presses" => self.mode = Mode::Presses,
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+}
+
+pub fn solve_all(problem: &Problem, options: &Options) -> Result<Vec<Outcome>> {
+    problem
+        .machines
+        .iter()
+        .map(|machine| machine.shifted(options.offset).solve(options.limit))
+        .collect()
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<i64> {
+    let outcomes = solve_all(problem, options)?;
+    if let Some(i) = outcomes.iter().position(|o| *o == Outcome::Unbounded) {
+        bail!("Machine {} has no cheapest way to win", i + 1);
+    }
+    let tokens: i128 = outcomes.iter().filter_map(Outcome::tokens).sum();
+    Ok(tokens.try_into()?)
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let mut output = String::new();
+    match options.mode {
+        Mode::Answer => writeln!(output, "{}", solve(problem, options)?)?,
+        Mode::Presses => {
+            let outcomes = solve_all(problem, options)?;
+            for (i, (machine, outcome)) in problem.machines.iter().zip(outcomes).enumerate() {
+                write!(output, "Machine {}: ", i + 1)?;
+                match outcome {
+                    Outcome::Win { presses, tokens } => {
+                        for (button, count) in machine.buttons.iter().zip(presses) {
+                            write!(output, "{}={} ", button.name, count)?;
+                        }
+                        writeln!(output, "({} tokens)", tokens)?;
+                    }
+                    Outcome::NoSolution => writeln!(output, "no solution")?,
+                    Outcome::Unbounded => writeln!(output, "unbounded")?,
+                }
+            }
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "Button A: X+94, Y+34
+Button B: X+22, Y+67
+Prize: X=8400, Y=5400
+
+Button A: X+26, Y+66
+Button B: X+67, Y+21
+Prize: X=12748, Y=12176
+
+Button A: X+17, Y+86
+Button B: X+84, Y+37
+Prize: X=7870, Y=6450
+
+Button A: X+69, Y+23
+Button B: X+27, Y+71
+Prize: X=18641, Y=10279
+";
+
+    fn options(offset: i64, limit: Option<i64>) -> Options {
+        Options {
+            offset,
+            limit,
+            mode: Mode::Answer,
+        }
+    }
+
+    fn presses(input: &str) -> Result<Option<Vec<i128>>> {
+        let machine: Machine = input.parse()?;
+        Ok(match machine.solve(None)? {
+            Outcome::Win { presses, .. } => Some(presses),
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn parts() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        assert_eq!(solve(&problem, &options(0, Some(100)))?, 480);
+        assert_eq!(
+            solve(&problem, &options(10000000000000, None))?,
+            875318608908
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn collinear() -> Result<()> {
+        let machine = |a: (i64, i64), b: (i64, i64), t: (i64, i64)| {
+            format!(
+                "Button A: X+{}, Y+{}\nButton B: X+{}, Y+{}\nPrize: X={}, Y={}",
+                a.0, a.1, b.0, b.1, t.0, t.1
+            )
+        };
+        assert_eq!(
+            presses(&machine((4, 2), (2, 1), (10, 5)))?,
+            Some(vec![0, 5])
+        );
+        assert_eq!(
+            presses(&machine((8, 4), (2, 1), (18, 9)))?,
+            Some(vec![2, 1])
+        );
+        assert_eq!(
+            presses(&machine((6, 3), (4, 2), (14, 7)))?,
+            Some(vec![1, 2])
+        );
+        assert_eq!(presses(&machine((4, 2), (2, 1), (10, 6)))?, None);
+        assert_eq!(presses(&machine((4, 2), (6, 3), (5, 2)))?, None);
+        assert_eq!(presses(&machine((0, 0), (0, 3), (0, 9)))?, Some(vec![0, 3]));
+
+        let line = |a: i64, b: i64, t: i64| {
+            format!("Button A: X{:+}\nButton B: X{:+}\nPrize: X={}", a, b, t)
+        };
+        assert_eq!(presses(&line(2, -1, 3))?, Some(vec![2, 1]));
+        assert_eq!(presses(&line(-2, 1, 3))?, Some(vec![0, 3]));
+        assert_eq!(presses(&line(4, 6, 9))?, None);
+        assert_eq!(presses(&line(3, 5, 7))?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn extended() -> Result<()> {
+        // C is a cheap shortcut for A + B.
+        let input = "Button A: X+3, Y+1
+Button B: X+1, Y+2
+Button C: X+4, Y+3, Cost=2
+Prize: X=14, Y=13";
+        assert_eq!(presses(input)?, Some(vec![0, 2, 3]));
+        let limited = input.replace("Cost=2", "Cost=2, Limit=1");
+        assert_eq!(presses(&limited)?, Some(vec![2, 4, 1]));
+
+        let input = "Button A: X+1, Z+1
+Button B: Y+1, Z+1
+Button C: X+1, Y+1, Cost=1
+Button D: X+1, Y+1, Z+1, Cost=2, Limit=2
+Prize: X=5, Y=5, Z=4";
+        assert_eq!(presses(input)?, Some(vec![1, 1, 2, 2]));
+
+        // Two free buttons need enumeration.
+        let input = "Button A: X+3\nButton B: X+5\nButton C: X+7, Cost=2\nPrize: X=20";
+        assert_eq!(presses(input)?, Some(vec![0, 4, 0]));
+        let limited = input.replace("X+5", "X+5, Limit=1");
+        assert_eq!(presses(&limited)?, Some(vec![2, 0, 2]));
+        Ok(())
+    }
+
+    #[test]
+    fn unbounded() -> Result<()> {
+        let machine: Machine = "Button A: X+1\nButton B: X-1, Cost=-4\nPrize: X=3".parse()?;
+        assert_eq!(machine.solve(None)?, Outcome::Unbounded);
+        assert_eq!(
+            machine.solve(Some(5))?,
+            Outcome::Win {
+                presses: vec![5, 2],
+                tokens: 7
+            }
+        );
+
+        // Two B and one C cost one token less and leave the claw in place.
+        let input = "Button A: X+1\nButton B: X-1, Cost=-1\nButton C: X+2, Cost=1\nPrize: X=3";
+        let machine: Machine = input.parse()?;
+        assert_eq!(machine.solve(None)?, Outcome::Unbounded);
+        assert_eq!(machine.solve(Some(5))?.tokens(), Some(-1));
+
+        // With positive costs the cheapest way is bounded even though the
+        // press counts are not.
+        let machine: Machine = input.replace("Cost=-1", "Cost=1").parse()?;
+        assert_eq!(
+            machine.solve(None)?,
+            Outcome::Win {
+                presses: vec![0, 1, 2],
+                tokens: 3
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn idle_buttons() -> Result<()> {
+        // 7 is not a sum of 4, 5 and 6, however often A is pressed.
+        let input = "Button A: X+0
+Button B: X+5
+Button C: X+6, Cost=1
+Button D: X+4, Cost=3
+Prize: X=7";
+        let machine: Machine = input.parse()?;
+        assert_eq!(machine.solve(None)?, Outcome::NoSolution);
+        let machine: Machine = input.replace("X=7", "X=9").parse()?;
+        assert_eq!(
+            machine.solve(None)?,
+            Outcome::Win {
+                presses: vec![0, 1, 0, 1],
+                tokens: 4
+            }
+        );
+
+        // A paying idle button is pressed as often as allowed.
+        let machine: Machine = "Button A: X+0, Cost=-2\nButton B: X+1\nPrize: X=2".parse()?;
+        assert_eq!(machine.solve(None)?, Outcome::Unbounded);
+        assert_eq!(machine.solve(Some(3))?.tokens(), Some(-4));
+        Ok(())
+    }
+
+    #[test]
+    fn bad_input() {
+        assert!("Button C: X+1\nPrize: X=1".parse::<Machine>().is_err());
+        assert!("Button A: Z+1\nPrize: X=1".parse::<Machine>().is_err());
+        assert!("Button A: X+1".parse::<Machine>().is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use std::{
+    fmt::Write as _,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+use regex::Regex;
+
+#[derive(Clone, Debug)]
+pub struct Robot {
+    pub x: i32,
+    pub y: i32,
+    pub vx: i32,
+    pub vy: i32,
+}
+
+impl FromStr for Robot {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let pattern = Regex::new(r"^p=(-?[0-9]+),(-?[0-9]+) v=(-?[0-9]+),(-?[0-9]+)$")?;
+        let c = pattern.captures(input).context("Regex mismatch")?;
+        let x: i32 = c.get(1).unwrap().as_str().parse()?;
+        let y: i32 = c.get(2).unwrap().as_str().parse()?;
+        let vx: i32 = c.get(3).unwrap().as_str().parse()?;
+        let vy: i32 = c.get(4).unwrap().as_str().parse()?;
+        Ok(Robot { x, y, vx, vy })
+    }
+}
+
+impl Robot {
+    pub fn position_at(&self, t: usize, w: i32, h: i32) -> (i32, i32) {
+        let x = self.x as i64 + self.vx as i64 * (t % w as usize) as i64;
+        let y = self.y as i64 + self.vy as i64 * (t % h as usize) as i64;
+        (x.rem_euclid(w as i64) as i32, y.rem_euclid(h as i64) as i32)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub robots: Vec<Robot>,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let robots: Vec<Robot> = input
+            .trim()
+            .lines()
+            .map(|s| s.parse::<Robot>())
+            .collect::<Result<_>>()?;
+        Ok(Problem { robots })
+    }
+}
+
+impl Problem {
+    // Smallest arena containing every robot's starting position.
+    pub fn infer_arena(&self) -> (i32, i32) {
+        let w = self.robots.iter().map(|r| r.x + 1).max().unwrap_or(1);
+        let h = self.robots.iter().map(|r| r.y + 1).max().unwrap_or(1);
+        (w, h)
+    }
+
+    pub fn positions_at(&self, t: usize, w: i32, h: i32) -> Vec<(i32, i32)> {
+        self.robots
+            .iter()
+            .map(|robot| robot.position_at(t, w, h))
+            .collect()
+    }
+
+    // Number of robots on each cell at tick t, indexed as [y][x].
+    pub fn density_at(&self, t: usize, w: i32, h: i32) -> Vec<Vec<u32>> {
+        let mut density = vec![vec![0; w as usize]; h as usize];
+        for (x, y) in self.positions_at(t, w, h) {
+            density[y as usize][x as usize] += 1;
+        }
+        density
+    }
+}
+
+// Counts of robots per region when the arena is split into `columns` x
+// `rows` equal regions. Robots on a cell that straddles a region boundary
+// (e.g. the middle line of an odd-sized arena) are not counted.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Partition {
+    pub counts: Vec<Vec<usize>>,
+}
+
+fn region_of(p: i32, size: i32, parts: usize) -> Option<usize> {
+    let (p, size, parts) = (p as i64, size as i64, parts as i64);
+    let region = p * parts / size;
+    let straddles = (region + 1) * size < (p + 1) * parts;
+    (!straddles).then_some(region as usize)
+}
+
+impl Partition {
+    pub fn new(positions: &[(i32, i32)], w: i32, h: i32, columns: usize, rows: usize) -> Self {
+        let mut counts = vec![vec![0; columns]; rows];
+        for &(x, y) in positions {
+            if let (Some(col), Some(row)) = (region_of(x, w, columns), region_of(y, h, rows)) {
+                counts[row][col] += 1;
+            }
+        }
+        Partition { counts }
+    }
+
+    pub fn safety_factor(&self) -> usize {
+        self.counts.iter().flatten().product()
+    }
+
+    pub fn heatmap(&self) -> String {
+        const SHADES: &[u8] = b" .:-=+*#%@";
+        let max = self.counts.iter().flatten().copied().max().unwrap_or(0);
+        let digits = max.to_string().len();
+        let mut out = String::new();
+        for row in &self.counts {
+            let cells: Vec<String> = row
+                .iter()
+                .map(|&count| format!("{:>digits$}", count))
+                .collect();
+            let shades: String = row
+                .iter()
+                .map(|&count| {
+                    SHADES[(count * (SHADES.len() - 1)).checked_div(max).unwrap_or(0)] as char
+                })
+                .collect();
+            writeln!(out, "{}  |{}|", cells.join(" "), shades).unwrap();
+        }
+        out
+    }
+}
+
+pub fn compute_safety_factor(positions: &[(i32, i32)], w: i32, h: i32) -> usize {
+    Partition::new(positions, w, h, 2, 2).safety_factor()
+}
+
+// Arena and analysis options shared by both parts. The arena defaults to
+// the smallest one containing all robots.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RunOptions {
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub ticks: usize,
+    pub grid: (usize, usize),
+    pub heatmap: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions {
+            width: None,
+            height: None,
+            ticks: 100,
+            grid: (2, 2),
+            heatmap: false,
+        }
+    }
+}
+
+impl RunOptions {
+    // Consumes `arg` (and its value from `args`) if it is a shared option.
+    pub fn parse_arg(
+        &mut self,
+        arg: &str,
+        args: &mut impl Iterator<Item = String>,
+    ) -> Result<bool> {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("Missing {} value", arg))
+        };
+        match arg {
+            "--width" => {
+                let width = value()?.parse()?;
+                ensure!(width > 0, "Width must be positive");
+                self.width = Some(width);
+            }
+            "--height" => {
+                let height = value()?.parse()?;
+                ensure!(height > 0, "Height must be positive");
+                self.height = Some(height);
+            }
+            "--ticks" => self.ticks = value()?.parse()?,
+            "--grid" => {
+                let grid = value()?;
+                let (columns, rows) = grid.split_once('x').context("Grid must be like 4x4")?;
+                self.grid = (columns.parse()?, rows.parse()?);
+                ensure!(self.grid.0 > 0 && self.grid.1 > 0, "Grid must be non-empty");
+            }
+            "--heatmap" => self.heatmap = true,
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if !self.parse_arg(&arg, &mut args)? {
+                bail!("Unknown argument: {}", arg);
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn arena(&self, problem: &Problem) -> Result<(i32, i32)> {
+        let (w, h) = problem.infer_arena();
+        let (w, h) = (self.width.unwrap_or(w), self.height.unwrap_or(h));
+        ensure!(w > 0 && h > 0, "Empty arena: {}x{}", w, h);
+        Ok((w, h))
+    }
+}
+
+fn intensity(count: u32, max: u32) -> u8 {
+    (count * 255).checked_div(max).unwrap_or(0) as u8
+}
+
+pub fn write_pgm(out: &mut impl Write, density: &[Vec<u32>]) -> Result<()> {
+    let h = density.len();
+    let w = density.first().map_or(0, |row| row.len());
+    let max = density.iter().flatten().copied().max().unwrap_or(0);
+    write!(out, "P5\n{} {}\n255\n", w, h)?;
+    for row in density {
+        let pixels: Vec<u8> = row.iter().map(|&count| intensity(count, max)).collect();
+        out.write_all(&pixels)?;
+    }
+    Ok(())
+}
+
+// Writes one `tick_NNNNN.pgm` per tick into `dir`.
+pub fn export_frames(problem: &Problem, w: i32, h: i32, ticks: &[usize], dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(dir)?;
+    for &t in ticks {
+        let path = dir.join(format!("tick_{:05}.pgm", t));
+        let mut out = BufWriter::new(File::create(path)?);
+        write_pgm(&mut out, &problem.density_at(t, w, h))?;
+        out.flush()?;
+    }
+    Ok(())
+}
+
+// Tiles the given ticks into a single PGM image, `columns` frames per row,
+// separated by one-pixel gray borders.
+pub fn export_contact_sheet(
+    problem: &Problem,
+    w: i32,
+    h: i32,
+    ticks: &[usize],
+    columns: usize,
+    path: &Path,
+) -> Result<()> {
+    ensure!(columns > 0, "Contact sheet needs at least one column");
+    let (w, h) = (w as usize, h as usize);
+    let rows = ticks.len().div_ceil(columns);
+    let sheet_w = columns * (w + 1) + 1;
+    let sheet_h = rows * (h + 1) + 1;
+    let mut pixels = vec![64u8; sheet_w * sheet_h];
+    for (k, &t) in ticks.iter().enumerate() {
+        let density = problem.density_at(t, w as i32, h as i32);
+        let max = density.iter().flatten().copied().max().unwrap_or(0);
+        let (top, left) = ((k / columns) * (h + 1) + 1, (k % columns) * (w + 1) + 1);
+        for (y, row) in density.iter().enumerate() {
+            for (x, &count) in row.iter().enumerate() {
+                pixels[(top + y) * sheet_w + left + x] = intensity(count, max);
+            }
+        }
+    }
+    let mut out = BufWriter::new(File::create(path)?);
+    write!(out, "P5\n{} {}\n255\n", sheet_w, sheet_h)?;
+    out.write_all(&pixels)?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn position_at() -> Result<()> {
+        let robot: Robot = "p=2,4 v=2,-3".parse()?;
+        assert_eq!(robot.position_at(0, 11, 7), (2, 4));
+        assert_eq!(robot.position_at(1, 11, 7), (4, 1));
+        assert_eq!(robot.position_at(5, 11, 7), (1, 3));
+        assert_eq!(robot.position_at(5 + 11 * 7 * 1000, 11, 7), (1, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn partition() -> Result<()> {
+        let problem: Problem = "p=0,0 v=0,0\np=5,0 v=0,0\np=6,6 v=0,0\np=10,3 v=0,0\n".parse()?;
+        assert_eq!(problem.infer_arena(), (11, 7));
+        let positions = problem.positions_at(0, 11, 7);
+        assert_eq!(
+            Partition::new(&positions, 11, 7, 2, 2).counts,
+            [[1, 0], [0, 1]]
+        );
+        assert_eq!(Partition::new(&positions, 11, 7, 3, 1).counts, [[1, 2, 1]]);
+        assert_eq!(
+            Partition::new(&positions, 11, 7, 11, 7)
+                .counts
+                .iter()
+                .flatten()
+                .sum::<usize>(),
+            4
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn arena() -> Result<()> {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(RunOptions::default()
+            .parse_args(args(&["--width", "0"]))
+            .is_err());
+        assert!(RunOptions::default()
+            .parse_args(args(&["--height", "-5"]))
+            .is_err());
+
+        // Robots left of and above the origin leave nothing to infer.
+        let problem: Problem = "p=-3,-2 v=1,1\n".parse()?;
+        assert!(RunOptions::default().arena(&problem).is_err());
+        let options = RunOptions::default().parse_args(args(&["--width", "4", "--height", "3"]))?;
+        assert_eq!(options.arena(&problem)?, (4, 3));
+        Ok(())
+    }
+
+    #[test]
+    fn pgm() -> Result<()> {
+        let problem: Problem = "p=0,0 v=1,0\np=1,0 v=0,0\n".parse()?;
+        let mut out = Vec::new();
+        write_pgm(&mut out, &problem.density_at(1, 3, 1))?;
+        assert_eq!(out, b"P5\n3 1\n255\n\x00\xff\x00");
+        Ok(())
+    }
+}
@@ -0,0 +1,601 @@
+use std::{
+    collections::BTreeSet,
+    fmt::{Display, Write},
+    ops::{Add, Mul, Neg},
+    path::PathBuf,
+    str::FromStr,
+};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+
+use replay::{FrameFormat, FrameRange, Replay};
+
+pub mod interactive;
+pub mod planner;
+pub mod replay;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point {
+    pub i: usize,
+    pub j: usize,
+}
+
+impl Point {
+    pub const UP: Point = Point {
+        i: -1isize as usize,
+        j: 0,
+    };
+    pub const RIGHT: Point = Point { i: 0, j: 1 };
+    pub const DOWN: Point = Point { i: 1, j: 0 };
+    pub const LEFT: Point = Point {
+        i: 0,
+        j: -1isize as usize,
+    };
+    pub const DIRS: [Point; 4] = [Point::UP, Point::RIGHT, Point::DOWN, Point::LEFT];
+
+    pub fn from_move(c: char) -> Result<Point> {
+        match c {
+            '^' => Ok(Point::UP),
+            '>' => Ok(Point::RIGHT),
+            'v' => Ok(Point::DOWN),
+            '<' => Ok(Point::LEFT),
+            _ => bail!("Invalid move: {}", c),
+        }
+    }
+
+    pub fn to_move(self) -> char {
+        match self {
+            Point::UP => '^',
+            Point::RIGHT => '>',
+            Point::DOWN => 'v',
+            Point::LEFT => '<',
+            _ => panic!("Not a direction: {:?}", self),
+        }
+    }
+}
+
+impl Add<Point> for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point {
+            i: self.i.wrapping_add(other.i),
+            j: self.j.wrapping_add(other.j),
+        }
+    }
+}
+
+impl Neg for Point {
+    type Output = Point;
+
+    fn neg(self) -> Point {
+        Point {
+            i: self.i.wrapping_neg(),
+            j: self.j.wrapping_neg(),
+        }
+    }
+}
+
+impl Mul<usize> for Point {
+    type Output = Point;
+
+    fn mul(self, m: usize) -> Point {
+        Point {
+            i: self.i.wrapping_mul(m),
+            j: self.j.wrapping_mul(m),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Cell {
+    Empty,
+    Block,
+    Box(usize),
+}
+
+// A box occupying `width` cells to the right of `pos`, inclusive.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Crate {
+    pub pos: Point,
+    pub width: usize,
+}
+
+impl Crate {
+    pub fn cells(self) -> impl Iterator<Item = Point> {
+        (0..self.width).map(move |k| self.pos + Point::RIGHT * k)
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct State {
+    pub map: Vec<Vec<Cell>>,
+    pub boxes: Vec<Crate>,
+    pub robot: Point,
+}
+
+impl State {
+    // Parses a map where every input column is expanded to `scale` cells.
+    // Boxes are written as `O` (one column) or `[`, `-`..., `]` (several).
+    pub fn parse(input: &str, scale: usize) -> Result<Self> {
+        ensure!(scale > 0, "Scale must be positive");
+        let mut map: Vec<Vec<Cell>> = Vec::new();
+        let mut boxes: Vec<Crate> = Vec::new();
+        let mut robots: Vec<Point> = Vec::new();
+
+        for (i, line) in input.lines().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            let mut row = vec![Cell::Empty; chars.len() * scale];
+            let mut k = 0;
+            while k < chars.len() {
+                let pos = Point { i, j: k * scale };
+                let len = match chars[k] {
+                    '.' => 1,
+                    '@' => {
+                        robots.push(pos);
+                        1
+                    }
+                    '#' => {
+                        row[pos.j..pos.j + scale].fill(Cell::Block);
+                        1
+                    }
+                    'O' | '[' => {
+                        let len = if chars[k] == 'O' {
+                            1
+                        } else {
+                            let dashes = chars[k + 1..].iter().take_while(|&&c| c == '-').count();
+                            ensure!(
+                                chars.get(k + dashes + 1) == Some(&']'),
+                                "Unterminated box at {}:{}",
+                                i,
+                                k
+                            );
+                            dashes + 2
+                        };
+                        let id = boxes.len();
+                        boxes.push(Crate {
+                            pos,
+                            width: len * scale,
+                        });
+                        row[pos.j..pos.j + len * scale].fill(Cell::Box(id));
+                        len
+                    }
+                    c => bail!("Invalid cell: {}", c),
+                };
+                k += len;
+            }
+            map.push(row);
+        }
+
+        ensure!(robots.len() == 1, "Invalid number of robots");
+        let robot = robots[0];
+
+        Ok(State { map, boxes, robot })
+    }
+
+    pub fn cell(&self, p: Point) -> Cell {
+        self.map
+            .get(p.i)
+            .and_then(|row| row.get(p.j))
+            .copied()
+            .unwrap_or(Cell::Block)
+    }
+
+    pub fn cell_mut(&mut self, p: Point) -> &mut Cell {
+        &mut self.map[p.i][p.j]
+    }
+
+    // Returns the boxes that move if the robot steps towards `dir`, or None
+    // if the robot is blocked.
+    pub fn plan_push(&self, dir: Point) -> Option<BTreeSet<usize>> {
+        let mut pushes = BTreeSet::new();
+        if !self.attempt_push(self.robot + dir, dir, &mut pushes) {
+            return None;
+        }
+        Some(pushes)
+    }
+
+    fn attempt_push(&self, p: Point, dir: Point, pushes: &mut BTreeSet<usize>) -> bool {
+        let id = match self.cell(p) {
+            Cell::Empty => return true,
+            Cell::Block => return false,
+            Cell::Box(id) => id,
+        };
+        if !pushes.insert(id) {
+            return true;
+        }
+
+        self.boxes[id].cells().all(|q| {
+            let next = q + dir;
+            self.cell(next) == Cell::Box(id) || self.attempt_push(next, dir, pushes)
+        })
+    }
+
+    pub fn make_move(&mut self, dir: Point) -> bool {
+        let Some(pushes) = self.plan_push(dir) else {
+            return false;
+        };
+        self.apply_push(&pushes, dir);
+        self.robot = self.robot + dir;
+        true
+    }
+
+    pub fn apply_push(&mut self, pushes: &BTreeSet<usize>, dir: Point) {
+        for &id in pushes {
+            for p in self.boxes[id].cells() {
+                *self.cell_mut(p) = Cell::Empty;
+            }
+        }
+        for &id in pushes {
+            let b = &mut self.boxes[id];
+            b.pos = b.pos + dir;
+            for p in self.boxes[id].cells() {
+                *self.cell_mut(p) = Cell::Box(id);
+            }
+        }
+    }
+}
+
+impl Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (i, row) in self.map.iter().enumerate() {
+            for (j, &cell) in row.iter().enumerate() {
+                let p = Point { i, j };
+                let c = match cell {
+                    _ if self.robot == p => '@',
+                    Cell::Empty => '.',
+                    Cell::Block => '#',
+                    Cell::Box(id) => {
+                        let b = self.boxes[id];
+                        if b.width == 1 {
+                            'O'
+                        } else if b.pos == p {
+                            '['
+                        } else if b.pos.j + b.width - 1 == j {
+                            ']'
+                        } else {
+                            '-'
+                        }
+                    }
+                };
+                write!(f, "{}", c)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+pub fn compute_gps_sum(state: &State) -> usize {
+    state.boxes.iter().map(|b| b.pos.i * 100 + b.pos.j).sum()
+}
+
+pub fn parse_moves(input: &str) -> Result<Vec<Point>> {
+    input
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(Point::from_move)
+        .collect()
+}
+
+pub fn format_moves(moves: &[Point]) -> String {
+    let line_len = 70;
+    let chars: Vec<char> = moves.iter().map(|dir| dir.to_move()).collect();
+    chars
+        .chunks(line_len)
+        .map(|chunk| chunk.iter().chain(['\n'].iter()).collect::<String>())
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub state: State,
+    pub moves: Vec<Point>,
+}
+
+impl Problem {
+    pub fn parse(input: &str, scale: usize) -> Result<Self> {
+        let (map_str, moves_str) = input.split_once("\n\n").context("Invalid input")?;
+        let state = State::parse(map_str, scale)?;
+        let moves = parse_moves(moves_str)?;
+        Ok(Problem { state, moves })
+    }
+
+    // Like parse, but the moves section may be left out.
+    pub fn parse_map(input: &str, scale: usize) -> Result<Self> {
+        match input.split_once("\n\n") {
+            Some(_) => Problem::parse(input, scale),
+            None => Ok(Problem {
+                state: State::parse(input, scale)?,
+                moves: Vec::new(),
+            }),
+        }
+    }
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        Problem::parse(input, 1)
+    }
+}
+
+pub fn solve(problem: &Problem) -> Result<usize> {
+    let mut state = problem.state.clone();
+
+    for &dir in &problem.moves {
+        state.make_move(dir);
+    }
+
+    Ok(compute_gps_sum(&state))
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Answer,
+    Show(usize),
+    Export {
+        dir: PathBuf,
+        format: FrameFormat,
+        from: usize,
+        to: Option<usize>,
+        every: usize,
+    },
+    Play {
+        map: PathBuf,
+        save: Option<PathBuf>,
+    },
+    Plan {
+        goal: PathBuf,
+        max_states: usize,
+    },
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub width: usize,
+    pub mode: Mode,
+}
+
+impl Options {
+    pub fn new(width: usize) -> Self {
+        Options {
+            width,
+            mode: Mode::Answer,
+        }
+    }
+
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        let mut format = FrameFormat::Ascii;
+        let mut from = 0;
+        let mut to = None;
+        let mut every = 1;
+        let mut export_dir = None;
+        let mut export_flag = None;
+        let mut save = None;
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .with_context(|| format!("Missing {} value", arg))
+            };
+            if ["--format", "--from", "--to", "--every"].contains(&arg.as_str()) {
+                export_flag = Some(arg.clone());
+            }
+            match arg.as_str() {
+                "--width" => self.width = value()?.parse()?,
+                "--show" => self.mode = Mode::Show(value()?.parse()?),
+                "--export" => export_dir = Some(PathBuf::from(value()?)),
+                "--format" => {
+                    format = match value()?.as_str() {
+                        "ascii" => FrameFormat::Ascii,
+                        "ppm" => FrameFormat::Ppm,
+                        f => bail!("Unknown frame format: {}", f),
+                    }
+                }
+                "--from" => from = value()?.parse()?,
+                "--to" => to = Some(value()?.parse()?),
+                "--every" => every = value()?.parse()?,
+                "--play" => {
+                    self.mode = Mode::Play {
+                        map: PathBuf::from(value()?),
+                        save: None,
+                    }
+                }
+                "--save" => save = Some(PathBuf::from(value()?)),
+                "--plan" => {
+                    self.mode = Mode::Plan {
+                        goal: PathBuf::from(value()?),
+                        max_states: 10_000_000,
+                    }
+                }
+                "--max-states" => {
+                    let limit = value()?.parse()?;
+                    if let Mode::Plan { max_states, .. } = &mut self.mode {
+                        *max_states = limit;
+                    } else {
+                        bail!("--max-states must follow --plan");
+                    }
+                }
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        if let (None, Some(flag)) = (&export_dir, export_flag) {
+            bail!("{} requires --export", flag);
+        }
+        if let Mode::Play {
+            save: mode_save, ..
+        } = &mut self.mode
+        {
+            *mode_save = save;
+        } else if save.is_some() {
+            bail!("--save requires --play");
+        } else if let Some(dir) = export_dir {
+            self.mode = Mode::Export {
+                dir,
+                format,
+                from,
+                to,
+                every,
+            };
+        }
+        Ok(self)
+    }
+}
+
+impl Options {
+    // The planner only needs the starting map, so its input may omit the
+    // moves section.
+    pub fn parse_problem(&self, input: &str) -> Result<Problem> {
+        match self.mode {
+            Mode::Plan { .. } => Problem::parse_map(input, self.width),
+            _ => Problem::parse(input, self.width),
+        }
+    }
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let mut output = String::new();
+    match &options.mode {
+        Mode::Answer => writeln!(output, "{}", solve(problem)?)?,
+        Mode::Show(step) => {
+            let mut replay = Replay::record(&problem.state, &problem.moves);
+            replay.seek(*step)?;
+            writeln!(output, "Step {}/{}", step, replay.len())?;
+            write!(output, "{}", replay.state())?;
+        }
+        Mode::Export {
+            dir,
+            format,
+            from,
+            to,
+            every,
+        } => {
+            let mut replay = Replay::record(&problem.state, &problem.moves);
+            let range = FrameRange {
+                from: *from,
+                to: to.unwrap_or(replay.len()),
+                every: *every,
+            };
+            let count = replay::export_frames(&mut replay, range, *format, dir)?;
+            writeln!(output, "Exported {} frames to {}", count, dir.display())?;
+        }
+        Mode::Play { .. } => bail!("Interactive mode does not take a puzzle input"),
+        Mode::Plan { goal, max_states } => {
+            let text = std::fs::read_to_string(goal)?;
+            let goal_str = text
+                .split_once("\n\n")
+                .map_or(text.as_str(), |(map, _)| map);
+            let goal = planner::Goal::parse(goal_str, options.width)?;
+            match planner::plan(&problem.state, &goal, *max_states)? {
+                Some(moves) => write!(output, "{}", format_moves(&moves))?,
+                None => bail!("Goal is unreachable"),
+            }
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+pub(crate) const SAMPLE: &str = "##########
+#..O..O.O#
+#......O.#
+#.OO..O.O#
+#..O@..O.#
+#O#..O...#
+#O..O..O.#
+#.OO.O.OO#
+#....O...#
+##########
+
+<vv>^<v^>v>^vv^v>v<>v^v<v<^vv<<<^><<><>>v<vvv<>^v^>^<<<><<v<<<v^vv^v>^
+vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
+><>vv>v^v^<>><>>>><^^>vv>v<^^^>>v^v^<^^>v^^>v^<^v>v<>>v^v^<v>v^^<^^vv<
+<<v<^>>^^^^>>>v^<>vvv^><v<<<>^^^vv^<vvv>^>v<^^^^v<>^>vvvv><>>v^<<^^^^^
+^><^><>>><>^^<<^^v>>><^<v>^<vv>>v>>>^v><>^v><<<<v>>v<v<v>vvv>^<><<>^><
+^>><>^v<><^vvv<^^<><v<<<<<><^v<<<><<<^^<v<^^^><^>>^<v^><<<^>>^v<v^v<v^
+>^>>^v>vv>^<<^v<>><<><<v<<v><>v<^vv<<<>^^v^>^^>>><<^v>>v^v><^^>>^<>vv^
+<><^^>^^^<><vvvvv^v<v<<>^v<v>v<<^><<><<><<<^^<<<^<<>><<><^^^>^^<>^>v<>
+^^>vv<^v^v<vv>^<><v<^v>^^^>>>^^vvv^>vvv<>>>^<^>>>>>^<<^v>^vvv<>^<><<v>
+v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn widths() -> Result<()> {
+        assert_eq!(solve(&Problem::parse(SAMPLE, 1)?)?, 10092);
+        assert_eq!(solve(&Problem::parse(SAMPLE, 2)?)?, 9021);
+        Ok(())
+    }
+
+    #[test]
+    fn mixed_widths() -> Result<()> {
+        let input = "#########
+#.......#
+#.[-]...#
+#..[]O..#
+#...@...#
+#########
+
+^<^
+";
+        let problem = Problem::parse(input, 1)?;
+        let mut state = problem.state.clone();
+        for &dir in &problem.moves {
+            state.make_move(dir);
+        }
+        assert_eq!(
+            state.to_string(),
+            "#########
+#.[-]...#
+#..[]...#
+#..@.O..#
+#.......#
+#########
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn missing_moves() -> Result<()> {
+        let input = "#####\n#@O.#\n#####\n";
+        assert!(Problem::parse(input, 1).is_err());
+        let problem = Problem::parse_map(input, 1)?;
+        assert!(problem.moves.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn export_flags() -> Result<()> {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(Options::new(1).parse_args(args(&["--from", "3"])).is_err());
+        let options = Options::new(1).parse_args(args(&["--every", "2", "--export", "out"]))?;
+        assert_eq!(
+            options.mode,
+            Mode::Export {
+                dir: PathBuf::from("out"),
+                format: FrameFormat::Ascii,
+                from: 0,
+                to: None,
+                every: 2,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn save_flag() -> Result<()> {
+        let args = |args: &[&str]| args.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        assert!(Options::new(1)
+            .parse_args(args(&["--save", "out"]))
+            .is_err());
+        let options = Options::new(1).parse_args(args(&["--play", "map.txt", "--save", "out"]))?;
+        assert!(matches!(options.mode, Mode::Play { save: Some(_), .. }));
+        Ok(())
+    }
+}
@@ -0,0 +1,291 @@
+use std::{
+    io::{IsTerminal, Read, Write},
+    path::Path,
+    process::{Command, Stdio},
+};
+
+use anyhow::Result;
+
+use super::{compute_gps_sum, format_moves, replay::Step, Point, State};
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Key {
+    Move(Point),
+    Undo,
+    Quit,
+    Ignore,
+}
+
+impl Key {
+    pub fn from_byte(b: u8) -> Key {
+        match b {
+            b'^' | b'w' | b'W' => Key::Move(Point::UP),
+            b'>' | b'd' | b'D' => Key::Move(Point::RIGHT),
+            b'v' | b's' | b'S' => Key::Move(Point::DOWN),
+            b'<' | b'a' | b'A' => Key::Move(Point::LEFT),
+            b'u' | b'U' | 0x08 | 0x7f => Key::Undo,
+            b'q' | b'Q' | 0x04 => Key::Quit,
+            _ => Key::Ignore,
+        }
+    }
+}
+
+// Turns input bytes into keys. Arrow keys arrive as escape sequences
+// (ESC [ A to ESC [ D, possibly with parameters like ESC [ 1 ; 5 A), which
+// are consumed whole so that their final byte is not read as a letter key.
+// In application cursor mode they are sent as ESC O A to ESC O D instead.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum KeyDecoder {
+    #[default]
+    Ground,
+    Escape,
+    Csi,
+}
+
+impl KeyDecoder {
+    // Returns None while in the middle of an escape sequence.
+    pub fn feed(&mut self, b: u8) -> Option<Key> {
+        match (*self, b) {
+            (_, 0x1b) => {
+                *self = KeyDecoder::Escape;
+                None
+            }
+            (KeyDecoder::Ground, _) => Some(Key::from_byte(b)),
+            (KeyDecoder::Escape, b'[' | b'O') => {
+                *self = KeyDecoder::Csi;
+                None
+            }
+            (KeyDecoder::Escape, _) => {
+                *self = KeyDecoder::Ground;
+                Some(Key::from_byte(b))
+            }
+            (KeyDecoder::Csi, 0x30..=0x3f) => None,
+            (KeyDecoder::Csi, _) => {
+                *self = KeyDecoder::Ground;
+                Some(match b {
+                    b'A' => Key::Move(Point::UP),
+                    b'B' => Key::Move(Point::DOWN),
+                    b'C' => Key::Move(Point::RIGHT),
+                    b'D' => Key::Move(Point::LEFT),
+                    _ => Key::Ignore,
+                })
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Session {
+    pub state: State,
+    history: Vec<Step>,
+}
+
+impl Session {
+    pub fn new(state: State) -> Self {
+        Session {
+            state,
+            history: Vec::new(),
+        }
+    }
+
+    pub fn make_move(&mut self, dir: Point) -> bool {
+        let step = Step::record(&mut self.state, dir);
+        let moved = step.moved;
+        self.history.push(step);
+        moved
+    }
+
+    pub fn undo(&mut self) -> bool {
+        let Some(step) = self.history.pop() else {
+            return false;
+        };
+        step.undo(&mut self.state);
+        true
+    }
+
+    pub fn moves(&self) -> Vec<Point> {
+        self.history.iter().map(|step| step.dir).collect()
+    }
+
+    pub fn handle(&mut self, key: Key) -> bool {
+        match key {
+            Key::Move(dir) => {
+                self.make_move(dir);
+            }
+            Key::Undo => {
+                self.undo();
+            }
+            Key::Quit => return false,
+            Key::Ignore => {}
+        }
+        true
+    }
+
+    pub fn draw(&self, out: &mut impl Write) -> Result<()> {
+        write!(out, "\x1b[2J\x1b[H{}", self.state)?;
+        writeln!(
+            out,
+            "GPS sum: {}  Moves: {}",
+            compute_gps_sum(&self.state),
+            self.history.len()
+        )?;
+        writeln!(out, "[arrows, ^>v< or WASD] move  [u] undo  [q] quit")?;
+        out.flush()?;
+        Ok(())
+    }
+}
+
+// Puts the terminal into non-canonical mode while alive so that keys are
+// delivered without waiting for Enter.
+struct RawTerminal;
+
+impl RawTerminal {
+    fn enable() -> Option<RawTerminal> {
+        if !std::io::stdin().is_terminal() {
+            return None;
+        }
+        let status = Command::new("stty")
+            .args(["-icanon", "-echo", "min", "1"])
+            .stdin(Stdio::inherit())
+            .status()
+            .ok()?;
+        status.success().then_some(RawTerminal)
+    }
+}
+
+impl Drop for RawTerminal {
+    fn drop(&mut self) {
+        let _ = Command::new("stty")
+            .args(["icanon", "echo"])
+            .stdin(Stdio::inherit())
+            .status();
+    }
+}
+
+pub fn run_session(
+    session: &mut Session,
+    mut input: impl Read,
+    out: &mut impl Write,
+) -> Result<()> {
+    session.draw(out)?;
+    let mut buf = [0];
+    let mut decoder = KeyDecoder::default();
+    while input.read(&mut buf)? > 0 {
+        let Some(key) = decoder.feed(buf[0]) else {
+            continue;
+        };
+        if !session.handle(key) {
+            break;
+        }
+        if key != Key::Ignore {
+            session.draw(out)?;
+        }
+    }
+    Ok(())
+}
+
+// Loads a map (optionally followed by moves, which are ignored), lets the
+// user play it from stdin and saves the map with the typed moves.
+pub fn play(map_path: &Path, save_path: Option<&Path>, scale: usize) -> Result<()> {
+    let text = std::fs::read_to_string(map_path)?;
+    let map_str = text
+        .split_once("\n\n")
+        .map_or(text.as_str(), |(map, _)| map);
+    let mut session = Session::new(State::parse(map_str, scale)?);
+
+    {
+        let _raw = RawTerminal::enable();
+        let stdout = std::io::stdout();
+        run_session(&mut session, std::io::stdin().lock(), &mut stdout.lock())?;
+    }
+
+    let moves = format_moves(&session.moves());
+    match save_path {
+        Some(path) => {
+            std::fs::write(path, format!("{}\n\n{}", map_str.trim_end(), moves))?;
+            println!(
+                "Saved {} moves to {}",
+                session.moves().len(),
+                path.display()
+            );
+        }
+        None => print!("{}", moves),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day15::Problem;
+
+    #[test]
+    fn session() -> Result<()> {
+        let problem = Problem::parse(
+            "#######
+#...#.#
+#.....#
+#..OO@#
+#..O..#
+#.....#
+#######
+
+<
+",
+            2,
+        )?;
+        let mut session = Session::new(problem.state.clone());
+        let mut out = Vec::new();
+        run_session(&mut session, "<vv<<^^x<<wq^".as_bytes(), &mut out)?;
+        assert_eq!(format_moves(&session.moves()), "<vv<<^^<<^\n");
+        assert_eq!(
+            session.state.to_string(),
+            "##############
+##......##..##
+##...[][]...##
+##...@[]....##
+##..........##
+##..........##
+##############
+"
+        );
+        assert_eq!(compute_gps_sum(&session.state), 205 + 207 + 306);
+
+        while session.undo() {}
+        assert_eq!(session.state, problem.state);
+        Ok(())
+    }
+
+    #[test]
+    fn arrows() {
+        let mut decoder = KeyDecoder::default();
+        let keys: Vec<Key> = b"\x1b[A\x1b[D\x1b[1;5CA\x1b[Hq"
+            .iter()
+            .filter_map(|&b| decoder.feed(b))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                Key::Move(Point::UP),
+                Key::Move(Point::LEFT),
+                Key::Move(Point::RIGHT),
+                Key::Move(Point::LEFT),
+                Key::Ignore,
+                Key::Quit,
+            ]
+        );
+
+        let keys: Vec<Key> = b"\x1bOA\x1bOCw"
+            .iter()
+            .filter_map(|&b| decoder.feed(b))
+            .collect();
+        assert_eq!(
+            keys,
+            [
+                Key::Move(Point::UP),
+                Key::Move(Point::RIGHT),
+                Key::Move(Point::UP),
+            ]
+        );
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::{hash_map::Entry, BTreeSet, HashMap, VecDeque};
+
+use anyhow::{bail, ensure, Result};
+
+use super::{Crate, Point, State};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Goal {
+    // Every box must end up exactly on one of the goal boxes.
+    Layout(Vec<Crate>),
+    // Every box must end up covering marked cells only.
+    Marked(BTreeSet<Point>),
+}
+
+impl Goal {
+    pub fn from_state(state: &State) -> Self {
+        Goal::Layout(sorted_boxes(state))
+    }
+
+    // A goal map with `x` cells is a Marked goal, where only the marks
+    // matter. Any other map is a Layout goal. The robot may end anywhere.
+    pub fn parse(input: &str, scale: usize) -> Result<Self> {
+        if !input.contains('x') {
+            return Ok(Goal::from_state(&State::parse(input, scale)?));
+        }
+        let mut marks = BTreeSet::new();
+        for (i, line) in input.lines().enumerate() {
+            for (k, c) in line.chars().enumerate() {
+                if c == 'x' {
+                    marks.extend((0..scale).map(|d| Point {
+                        i,
+                        j: k * scale + d,
+                    }));
+                }
+            }
+        }
+        Ok(Goal::Marked(marks))
+    }
+
+    pub fn is_reached(&self, state: &State) -> bool {
+        match self {
+            Goal::Layout(boxes) => sorted_boxes(state) == *boxes,
+            Goal::Marked(marks) => state
+                .boxes
+                .iter()
+                .all(|b| b.cells().all(|p| marks.contains(&p))),
+        }
+    }
+}
+
+fn sorted_boxes(state: &State) -> Vec<Crate> {
+    let mut boxes = state.boxes.clone();
+    boxes.sort();
+    boxes
+}
+
+fn widths(boxes: &[Crate]) -> Vec<usize> {
+    let mut widths: Vec<usize> = boxes.iter().map(|b| b.width).collect();
+    widths.sort();
+    widths
+}
+
+// Breadth-first search over (robot, box layout) for the shortest move list
+// reaching the goal. Fails once more than `max_states` layouts are seen.
+pub fn plan(start: &State, goal: &Goal, max_states: usize) -> Result<Option<Vec<Point>>> {
+    match goal {
+        Goal::Layout(boxes) => ensure!(
+            widths(&start.boxes) == widths(boxes),
+            "Goal must have the same boxes as the start"
+        ),
+        Goal::Marked(marks) => ensure!(
+            start.boxes.iter().map(|b| b.width).sum::<usize>() <= marks.len(),
+            "Not enough marked cells for the boxes"
+        ),
+    }
+
+    let key = |state: &State| (state.robot, sorted_boxes(state));
+    let mut parents: Vec<Option<(usize, Point)>> = vec![None];
+    let mut seen = HashMap::from([(key(start), 0)]);
+    let mut queue = VecDeque::from([(start.clone(), 0)]);
+
+    while let Some((state, index)) = queue.pop_front() {
+        if goal.is_reached(&state) {
+            let mut moves = Vec::new();
+            let mut current = index;
+            while let Some((parent, dir)) = parents[current] {
+                moves.push(dir);
+                current = parent;
+            }
+            moves.reverse();
+            return Ok(Some(moves));
+        }
+
+        for dir in Point::DIRS {
+            let mut next = state.clone();
+            if !next.make_move(dir) {
+                continue;
+            }
+            if let Entry::Vacant(entry) = seen.entry(key(&next)) {
+                if parents.len() >= max_states {
+                    bail!("Gave up after exploring {} states", max_states);
+                }
+                entry.insert(parents.len());
+                queue.push_back((next, parents.len()));
+                parents.push(Some((index, dir)));
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day15::format_moves;
+
+    #[test]
+    fn plan_wide() -> Result<()> {
+        let start = State::parse(
+            "######
+#....#
+#.O..#
+#.@..#
+######",
+            2,
+        )?;
+        let goal = Goal::from_state(&State::parse(
+            "######
+#.O..#
+#....#
+#...@#
+######",
+            2,
+        )?);
+        let moves = plan(&start, &goal, 10000)?.unwrap();
+        assert_eq!(format_moves(&moves), "^\n");
+
+        let goal = Goal::from_state(&State::parse(
+            "######
+#....#
+#...O#
+#@...#
+######",
+            2,
+        )?);
+        let moves = plan(&start, &goal, 10000)?.unwrap();
+        let mut state = start.clone();
+        for &dir in &moves {
+            state.make_move(dir);
+        }
+        assert!(goal.is_reached(&state));
+        assert_eq!(moves.len(), 6);
+        Ok(())
+    }
+
+    #[test]
+    fn unreachable() -> Result<()> {
+        let start = State::parse("#####\n#@O.#\n#####", 1)?;
+        let goal = Goal::from_state(&State::parse("#####\n#O@.#\n#####", 1)?);
+        assert_eq!(plan(&start, &goal, 100)?, None);
+        Ok(())
+    }
+
+    #[test]
+    fn marked() -> Result<()> {
+        let start = State::parse("#######\n#@O.O.#\n#.....#\n#######", 1)?;
+        let goal = Goal::parse("#######\n#...xx#\n#.....#\n#######", 1)?;
+        let moves = plan(&start, &goal, 10000)?.unwrap();
+        assert_eq!(format_moves(&moves), ">>\n");
+
+        // Wide boxes must cover marks with both halves.
+        let start = State::parse("######\n#@O..#\n######", 2)?;
+        let goal = Goal::parse("######\n#...x#\n######", 2)?;
+        let moves = plan(&start, &goal, 10000)?.unwrap();
+        assert_eq!(format_moves(&moves), ">>>>>\n");
+        let goal = Goal::parse("######\n#x...#\n######", 1)?;
+        assert!(plan(&start, &goal, 10000).is_err());
+        Ok(())
+    }
+}
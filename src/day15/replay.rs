@@ -0,0 +1,262 @@
+use std::{
+    collections::BTreeSet,
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use anyhow::{ensure, Result};
+
+use super::{Cell, Point, State};
+
+// The change made by a single move. Blocked moves are recorded too so that
+// step numbers line up with the move list.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Step {
+    pub dir: Point,
+    pub moved: bool,
+    pub pushes: BTreeSet<usize>,
+}
+
+impl Step {
+    // Makes a move on the state and records what changed.
+    pub fn record(state: &mut State, dir: Point) -> Step {
+        match state.plan_push(dir) {
+            Some(pushes) => {
+                state.apply_push(&pushes, dir);
+                state.robot = state.robot + dir;
+                Step {
+                    dir,
+                    moved: true,
+                    pushes,
+                }
+            }
+            None => Step {
+                dir,
+                moved: false,
+                pushes: BTreeSet::new(),
+            },
+        }
+    }
+
+    pub fn redo(&self, state: &mut State) {
+        if self.moved {
+            state.apply_push(&self.pushes, self.dir);
+            state.robot = state.robot + self.dir;
+        }
+    }
+
+    pub fn undo(&self, state: &mut State) {
+        if self.moved {
+            state.apply_push(&self.pushes, -self.dir);
+            state.robot = state.robot + -self.dir;
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Replay {
+    steps: Vec<Step>,
+    state: State,
+    position: usize,
+}
+
+impl Replay {
+    pub fn record(initial: &State, moves: &[Point]) -> Self {
+        let mut state = initial.clone();
+        let steps = moves
+            .iter()
+            .map(|&dir| Step::record(&mut state, dir))
+            .collect();
+        Replay {
+            steps,
+            state: initial.clone(),
+            position: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn state(&self) -> &State {
+        &self.state
+    }
+
+    pub fn forward(&mut self) -> bool {
+        let Some(step) = self.steps.get(self.position) else {
+            return false;
+        };
+        step.redo(&mut self.state);
+        self.position += 1;
+        true
+    }
+
+    pub fn backward(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.position -= 1;
+        self.steps[self.position].undo(&mut self.state);
+        true
+    }
+
+    pub fn seek(&mut self, position: usize) -> Result<()> {
+        ensure!(
+            position <= self.len(),
+            "Step {} is out of range (0..={})",
+            position,
+            self.len()
+        );
+        while self.position < position {
+            self.forward();
+        }
+        while self.position > position {
+            self.backward();
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameFormat {
+    Ascii,
+    Ppm,
+}
+
+// Frames to export: steps `from`, `from +/- every`, ... up to `to`. Frames
+// are played backward if `from > to`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FrameRange {
+    pub from: usize,
+    pub to: usize,
+    pub every: usize,
+}
+
+impl FrameRange {
+    pub fn positions(self) -> Vec<usize> {
+        let every = self.every.max(1);
+        if self.from <= self.to {
+            (self.from..=self.to).step_by(every).collect()
+        } else {
+            (self.to..=self.from).rev().step_by(every).collect()
+        }
+    }
+}
+
+const PIXELS_PER_CELL: usize = 4;
+
+pub fn render_ppm(state: &State, out: &mut impl Write) -> Result<()> {
+    let height = state.map.len();
+    let width = state.map.first().map_or(0, |row| row.len());
+    writeln!(
+        out,
+        "P6\n{} {}\n255",
+        width * PIXELS_PER_CELL,
+        height * PIXELS_PER_CELL
+    )?;
+    for (i, row) in state.map.iter().enumerate() {
+        let colors: Vec<[u8; 3]> = row
+            .iter()
+            .enumerate()
+            .map(|(j, &cell)| match cell {
+                _ if state.robot == (Point { i, j }) => [255, 64, 64],
+                Cell::Empty => [16, 16, 16],
+                Cell::Block => [128, 128, 128],
+                Cell::Box(id) if id % 2 == 0 => [192, 144, 64],
+                Cell::Box(_) => [144, 96, 32],
+            })
+            .collect();
+        for _ in 0..PIXELS_PER_CELL {
+            for color in &colors {
+                for _ in 0..PIXELS_PER_CELL {
+                    out.write_all(color)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+// Writes ASCII frames as one `frame_NNNNN.txt` per step, and PPM frames
+// back-to-back into `animation.ppm` (e.g. `ffmpeg -f ppm_pipe -i`).
+pub fn export_frames(
+    replay: &mut Replay,
+    range: FrameRange,
+    format: FrameFormat,
+    dir: &Path,
+) -> Result<usize> {
+    ensure!(
+        range.from.max(range.to) <= replay.len(),
+        "Frame range exceeds {} steps",
+        replay.len()
+    );
+    std::fs::create_dir_all(dir)?;
+    let positions = range.positions();
+    let mut animation = match format {
+        FrameFormat::Ascii => None,
+        FrameFormat::Ppm => Some(BufWriter::new(File::create(dir.join("animation.ppm"))?)),
+    };
+    for &position in &positions {
+        replay.seek(position)?;
+        match &mut animation {
+            None => std::fs::write(
+                dir.join(format!("frame_{:05}.txt", position)),
+                replay.state().to_string(),
+            )?,
+            Some(out) => render_ppm(replay.state(), out)?,
+        }
+    }
+    if let Some(mut out) = animation {
+        out.flush()?;
+    }
+    Ok(positions.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day15::{Problem, SAMPLE};
+
+    #[test]
+    fn seek() -> Result<()> {
+        let problem = Problem::parse(SAMPLE, 2)?;
+        let mut replay = Replay::record(&problem.state, &problem.moves);
+        assert_eq!(replay.len(), problem.moves.len());
+
+        let mut state = problem.state.clone();
+        for &dir in &problem.moves[..300] {
+            state.make_move(dir);
+        }
+        replay.seek(replay.len())?;
+        replay.seek(300)?;
+        assert_eq!(replay.state(), &state);
+
+        replay.seek(0)?;
+        assert_eq!(replay.state(), &problem.state);
+        assert!(!replay.backward());
+        Ok(())
+    }
+
+    #[test]
+    fn frame_range() {
+        let range = FrameRange {
+            from: 10,
+            to: 0,
+            every: 4,
+        };
+        assert_eq!(range.positions(), [10, 6, 2]);
+    }
+}
@@ -0,0 +1,482 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fmt::Write,
+    ops::Add,
+    str::FromStr,
+};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+
+use crate::parallel;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct Point {
+    pub i: isize,
+    pub j: isize,
+}
+
+impl Point {
+    pub const RIGHT: Point = Point { i: 0, j: 1 };
+    pub const DOWN: Point = Point { i: 1, j: 0 };
+    pub const LEFT: Point = Point { i: 0, j: -1 };
+    pub const UP: Point = Point { i: -1, j: 0 };
+    pub const DIRS: [Point; 4] = [Point::RIGHT, Point::DOWN, Point::LEFT, Point::UP];
+
+    pub fn new(i: isize, j: isize) -> Self {
+        Point { i, j }
+    }
+}
+
+impl Add<Point> for Point {
+    type Output = Point;
+
+    fn add(self, other: Point) -> Point {
+        Point {
+            i: self.i + other.i,
+            j: self.j + other.j,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub width: usize,
+    pub height: usize,
+    pub track: Vec<bool>,
+    pub start: Point,
+    pub goal: Point,
+}
+
+impl Problem {
+    pub fn index(&self, p: Point) -> Option<usize> {
+        if p.i < 0 || p.j < 0 || p.i as usize >= self.height || p.j as usize >= self.width {
+            return None;
+        }
+        Some(p.i as usize * self.width + p.j as usize)
+    }
+
+    pub fn is_track(&self, p: Point) -> bool {
+        self.index(p).is_some_and(|index| self.track[index])
+    }
+
+    // Draws the map with the cheat marked as `1` (start), `2` (end) and `*`
+    // (the shortcut in between, vertical leg first).
+    pub fn render_cheat(&self, cheat: &Cheat) -> String {
+        let mut canvas: Vec<Vec<char>> = (0..self.height)
+            .map(|i| {
+                (0..self.width)
+                    .map(|j| {
+                        if self.track[i * self.width + j] {
+                            '.'
+                        } else {
+                            '#'
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        let mut draw = |p: Point, c: char| {
+            if self.index(p).is_some() {
+                canvas[p.i as usize][p.j as usize] = c;
+            }
+        };
+        draw(self.start, 'S');
+        draw(self.goal, 'E');
+
+        let mut p = cheat.start;
+        while p != cheat.end {
+            p = if p.i != cheat.end.i {
+                p + Point::new((cheat.end.i - p.i).signum(), 0)
+            } else {
+                p + Point::new(0, (cheat.end.j - p.j).signum())
+            };
+            draw(p, '*');
+        }
+        draw(cheat.start, '1');
+        draw(cheat.end, '2');
+
+        canvas
+            .into_iter()
+            .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+            .collect()
+    }
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let lines: Vec<&str> = input.lines().collect();
+        let height = lines.len();
+        let width = lines.first().map_or(0, |line| line.len());
+        let mut track = vec![false; width * height];
+        let mut starts: Vec<Point> = Vec::new();
+        let mut goals: Vec<Point> = Vec::new();
+        for (i, line) in lines.iter().enumerate() {
+            ensure!(line.len() == width, "Inconsistent line length");
+            for (j, c) in line.chars().enumerate() {
+                let p = Point::new(i as isize, j as isize);
+                match c {
+                    '.' => {}
+                    'S' => starts.push(p),
+                    'E' => goals.push(p),
+                    '#' => continue,
+                    _ => bail!("Invalid character: {}", c),
+                }
+                track[i * width + j] = true;
+            }
+        }
+
+        ensure!(starts.len() == 1, "Expected exactly one start");
+        ensure!(goals.len() == 1, "Expected exactly one goal");
+
+        let start = starts[0];
+        let goal = goals[0];
+
+        Ok(Problem {
+            width,
+            height,
+            track,
+            start,
+            goal,
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DistanceMap {
+    pub distances: Vec<Option<usize>>,
+}
+
+impl DistanceMap {
+    pub fn new(problem: &Problem, start: Point) -> Self {
+        let mut distances = vec![None; problem.track.len()];
+        if let Some(index) = problem.index(start) {
+            distances[index] = Some(0);
+        }
+        let mut queue = VecDeque::from([(start, 0)]);
+
+        while let Some((current_pos, current_dist)) = queue.pop_front() {
+            for dir in Point::DIRS {
+                let next_pos = current_pos + dir;
+                let next_dist = current_dist + 1;
+                if !problem.is_track(next_pos) {
+                    continue;
+                }
+                let index = problem.index(next_pos).unwrap();
+                if distances[index].is_none() {
+                    distances[index] = Some(next_dist);
+                    queue.push_back((next_pos, next_dist));
+                }
+            }
+        }
+
+        DistanceMap { distances }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct Cheat {
+    pub start: Point,
+    pub end: Point,
+    pub saving: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct CheatFinder<'a> {
+    pub problem: &'a Problem,
+    pub radius: usize,
+    pub original_distance: usize,
+    from_start: DistanceMap,
+    from_goal: DistanceMap,
+}
+
+impl<'a> CheatFinder<'a> {
+    pub fn new(problem: &'a Problem, radius: usize) -> Result<Self> {
+        let from_start = DistanceMap::new(problem, problem.start);
+        let from_goal = DistanceMap::new(problem, problem.goal);
+        let original_distance =
+            from_start.distances[problem.index(problem.goal).unwrap()].context("No path found")?;
+        Ok(CheatFinder {
+            problem,
+            radius,
+            original_distance,
+            from_start,
+            from_goal,
+        })
+    }
+
+    // Visits the cheats starting at the given cell that save at least one
+    // picosecond, scanning the Manhattan diamond around it.
+    pub fn scan_from(&self, cheat_start: Point, visit: &mut impl FnMut(Cheat)) {
+        let Some(start_dist) = self
+            .problem
+            .index(cheat_start)
+            .and_then(|index| self.from_start.distances[index])
+        else {
+            return;
+        };
+        let radius = self.radius as isize;
+        for di in -radius..=radius {
+            let span = radius - di.abs();
+            for dj in -span..=span {
+                let cheat_end = cheat_start + Point::new(di, dj);
+                let Some(goal_dist) = self
+                    .problem
+                    .index(cheat_end)
+                    .and_then(|index| self.from_goal.distances[index])
+                else {
+                    continue;
+                };
+                let cheat_len = (di.abs() + dj.abs()) as usize;
+                let cheat_distance = start_dist + cheat_len + goal_dist;
+                if cheat_distance < self.original_distance {
+                    visit(Cheat {
+                        start: cheat_start,
+                        end: cheat_end,
+                        saving: self.original_distance - cheat_distance,
+                    });
+                }
+            }
+        }
+    }
+
+    // Runs `scan_row` over the rows of the map in parallel and merges the
+    // per-thread results.
+    pub fn par_rows<T: Send>(
+        &self,
+        init: impl Fn() -> T + Sync,
+        scan_row: impl Fn(&mut T, isize) + Sync,
+        merge: impl Fn(T, T) -> T,
+    ) -> T {
+        let rows: Vec<isize> = (0..self.problem.height as isize).collect();
+        let results = parallel::map_chunks(&rows, |rows| {
+            let mut acc = init();
+            for &i in rows {
+                scan_row(&mut acc, i);
+            }
+            acc
+        });
+        results.into_iter().fold(init(), merge)
+    }
+
+    pub fn count(&self, min_save: usize) -> usize {
+        self.par_rows(
+            || 0,
+            |count, i| {
+                for j in 0..self.problem.width as isize {
+                    self.scan_from(Point::new(i, j), &mut |cheat| {
+                        if cheat.saving >= min_save {
+                            *count += 1;
+                        }
+                    });
+                }
+            },
+            |a, b| a + b,
+        )
+    }
+
+    pub fn histogram(&self) -> BTreeMap<usize, usize> {
+        self.par_rows(
+            BTreeMap::new,
+            |histogram, i| {
+                for j in 0..self.problem.width as isize {
+                    self.scan_from(Point::new(i, j), &mut |cheat| {
+                        *histogram.entry(cheat.saving).or_default() += 1;
+                    });
+                }
+            },
+            |mut a, b| {
+                for (saving, count) in b {
+                    *a.entry(saving).or_default() += count;
+                }
+                a
+            },
+        )
+    }
+
+    // Lists cheats saving at least `min_save`, best savings first.
+    pub fn cheats(&self, min_save: usize) -> Vec<Cheat> {
+        let mut cheats = self.par_rows(
+            Vec::new,
+            |cheats, i| {
+                for j in 0..self.problem.width as isize {
+                    self.scan_from(Point::new(i, j), &mut |cheat| {
+                        if cheat.saving >= min_save {
+                            cheats.push(cheat);
+                        }
+                    });
+                }
+            },
+            |mut a, mut b| {
+                a.append(&mut b);
+                a
+            },
+        );
+        cheats.sort_by_key(|cheat| (std::cmp::Reverse(cheat.saving), cheat.start, cheat.end));
+        cheats
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Count,
+    Histogram,
+    List,
+    Show(usize),
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub radius: usize,
+    pub min_save: usize,
+    pub mode: Mode,
+}
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--radius" => {
+                    self.radius = args.next().context("Missing --radius value")?.parse()?;
+                }
+                "--min-save" => {
+                    self.min_save = args.next().context("Missing --min-save value")?.parse()?;
+                }
+                "--histogram" => self.mode = Mode::Histogram,
+                "--list" => self.mode = Mode::List,
+                "--show" => {
+                    let rank = args.next().context("Missing --show value")?.parse()?;
+                    self.mode = Mode::Show(rank);
+                }
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<usize> {
+    let finder = CheatFinder::new(problem, options.radius)?;
+    Ok(finder.count(options.min_save))
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let finder = CheatFinder::new(problem, options.radius)?;
+    let mut output = String::new();
+    match options.mode {
+        Mode::Count => writeln!(output, "{}", finder.count(options.min_save))?,
+        Mode::Histogram => {
+            for (saving, count) in finder.histogram() {
+                if saving >= options.min_save {
+                    writeln!(output, "{} cheats save {} picoseconds", count, saving)?;
+                }
+            }
+        }
+        Mode::List => {
+            for cheat in finder.cheats(options.min_save) {
+                writeln!(
+                    output,
+                    "({}, {}) -> ({}, {}): {}",
+                    cheat.start.i, cheat.start.j, cheat.end.i, cheat.end.j, cheat.saving
+                )?;
+            }
+        }
+        Mode::Show(rank) => {
+            let cheats = finder.cheats(options.min_save);
+            let cheat = cheats.get(rank).context("No such cheat")?;
+            writeln!(output, "Saves {} picoseconds", cheat.saving)?;
+            output.push_str(&problem.render_cheat(cheat));
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+pub(crate) const SAMPLE: &str = "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..E#...#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############
+";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radius() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        let count = |radius, min_save| {
+            solve(
+                &problem,
+                &Options {
+                    radius,
+                    min_save,
+                    mode: Mode::Count,
+                },
+            )
+        };
+        assert_eq!(count(2, 1)?, 44);
+        assert_eq!(count(2, 64)?, 1);
+        assert_eq!(count(20, 50)?, 285);
+        assert_eq!(count(20, 76)?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn histogram() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        let finder = CheatFinder::new(&problem, 2)?;
+        assert_eq!(
+            finder.histogram(),
+            BTreeMap::from([
+                (2, 14),
+                (4, 14),
+                (6, 2),
+                (8, 4),
+                (10, 2),
+                (12, 3),
+                (20, 1),
+                (36, 1),
+                (38, 1),
+                (40, 1),
+                (64, 1),
+            ])
+        );
+
+        let cheats = finder.cheats(40);
+        assert_eq!(cheats.len(), 2);
+        assert_eq!(
+            problem.render_cheat(&cheats[0]),
+            "###############
+#...#...#.....#
+#.#.#.#.#.###.#
+#S#...#.#.#...#
+#######.#.#.###
+#######.#.#...#
+#######.#.###.#
+###..2*1..#...#
+###.#######.###
+#...###...#...#
+#.#####.#.###.#
+#.#...#.#.#...#
+#.#.#.#.#.#.###
+#...#...#...###
+###############
+"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,72 @@
+use std::{
+    collections::{HashMap, HashSet},
+    str::FromStr,
+};
+
+use anyhow::{Context, Error, Result};
+
+pub mod clique;
+pub mod kclique;
+pub mod report;
+
+pub type Node = String;
+
+#[derive(Clone, Debug, Default)]
+pub struct Graph {
+    pub edges: HashMap<Node, HashSet<Node>>,
+}
+
+impl FromStr for Graph {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut edges: HashMap<Node, HashSet<Node>> = HashMap::new();
+        for line in input.lines() {
+            let (a, b) = line.split_once('-').context("Invalid edge")?;
+            edges
+                .entry(a.to_string())
+                .or_default()
+                .insert(b.to_string());
+            edges
+                .entry(b.to_string())
+                .or_default()
+                .insert(a.to_string());
+        }
+        Ok(Graph { edges })
+    }
+}
+
+#[cfg(test)]
+pub(crate) const SAMPLE: &str = "kh-tc
+qp-kh
+de-cg
+ka-co
+yn-aq
+qp-ub
+cg-tb
+vc-aq
+tb-ka
+wh-tc
+yn-cg
+kh-ub
+ta-co
+de-co
+tc-td
+tb-wq
+wh-td
+ta-ka
+td-qp
+aq-cg
+wq-ub
+ub-vc
+de-ta
+wq-aq
+wq-vc
+wh-yn
+ka-de
+kh-ta
+co-tc
+wh-qp
+tb-vc
+td-yn
+";
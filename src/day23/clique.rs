@@ -0,0 +1,293 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use super::{Graph, Node};
+
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct BitSet {
+    words: Vec<u64>,
+}
+
+impl BitSet {
+    pub fn new(capacity: usize) -> Self {
+        BitSet {
+            words: vec![0; capacity.div_ceil(64)],
+        }
+    }
+
+    pub fn insert(&mut self, i: usize) {
+        self.words[i / 64] |= 1 << (i % 64);
+    }
+
+    pub fn remove(&mut self, i: usize) {
+        self.words[i / 64] &= !(1 << (i % 64));
+    }
+
+    pub fn contains(&self, i: usize) -> bool {
+        self.words
+            .get(i / 64)
+            .is_some_and(|w| w & (1 << (i % 64)) != 0)
+    }
+
+    pub fn len(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn intersection(&self, other: &BitSet) -> BitSet {
+        BitSet {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(a, b)| a & b)
+                .collect(),
+        }
+    }
+
+    pub fn intersection_len(&self, other: &BitSet) -> usize {
+        self.words
+            .iter()
+            .zip(&other.words)
+            .map(|(a, b)| (a & b).count_ones() as usize)
+            .sum()
+    }
+
+    pub fn difference(&self, other: &BitSet) -> BitSet {
+        BitSet {
+            words: self
+                .words
+                .iter()
+                .zip(&other.words)
+                .map(|(a, b)| a & !b)
+                .collect(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        self.words.iter().enumerate().flat_map(|(k, &word)| {
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some(k * 64 + bit)
+            })
+        })
+    }
+}
+
+// Graph with node names interned to dense ids in sorted name order.
+#[derive(Clone, Debug)]
+pub struct IndexedGraph {
+    names: Vec<Node>,
+    ids: HashMap<Node, usize>,
+    adjacency: Vec<BitSet>,
+}
+
+impl IndexedGraph {
+    pub fn new(graph: &Graph) -> Self {
+        let mut names: Vec<Node> = graph.edges.keys().cloned().collect();
+        names.sort();
+        let ids: HashMap<Node, usize> = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (name.clone(), id))
+            .collect();
+        let adjacency = names
+            .iter()
+            .map(|name| {
+                let mut neighbors = BitSet::new(names.len());
+                for other in &graph.edges[name] {
+                    neighbors.insert(ids[other]);
+                }
+                neighbors
+            })
+            .collect();
+        IndexedGraph {
+            names,
+            ids,
+            adjacency,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn name(&self, id: usize) -> &str {
+        &self.names[id]
+    }
+
+    pub fn id(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn neighbors(&self, id: usize) -> &BitSet {
+        &self.adjacency[id]
+    }
+
+    pub fn is_adjacent(&self, a: usize, b: usize) -> bool {
+        self.adjacency[a].contains(b)
+    }
+
+    pub fn degeneracy_order(&self) -> Vec<usize> {
+        let mut degrees: Vec<usize> = self.adjacency.iter().map(|n| n.len()).collect();
+        let mut queue: BTreeSet<(usize, usize)> = degrees
+            .iter()
+            .enumerate()
+            .map(|(id, &degree)| (degree, id))
+            .collect();
+        let mut removed = BitSet::new(self.len());
+        let mut order = Vec::with_capacity(self.len());
+        while let Some((_, id)) = queue.pop_first() {
+            removed.insert(id);
+            order.push(id);
+            for other in self.adjacency[id].difference(&removed).iter() {
+                queue.remove(&(degrees[other], other));
+                degrees[other] -= 1;
+                queue.insert((degrees[other], other));
+            }
+        }
+        order
+    }
+
+    // Bron-Kerbosch with Tomita pivoting, with the outermost level iterated in
+    // degeneracy order.
+    pub fn for_each_maximal_clique(&self, mut visit: impl FnMut(&[usize])) {
+        let order = self.degeneracy_order();
+        let mut visited = BitSet::new(self.len());
+        let mut clique = Vec::new();
+        for id in order {
+            let neighbors = &self.adjacency[id];
+            let candidates = neighbors.difference(&visited);
+            let excluded = neighbors.intersection(&visited);
+            clique.push(id);
+            self.expand(&mut clique, candidates, excluded, &mut visit);
+            clique.pop();
+            visited.insert(id);
+        }
+    }
+
+    fn expand(
+        &self,
+        clique: &mut Vec<usize>,
+        mut candidates: BitSet,
+        mut excluded: BitSet,
+        visit: &mut impl FnMut(&[usize]),
+    ) {
+        if candidates.is_empty() {
+            if excluded.is_empty() {
+                visit(clique);
+            }
+            return;
+        }
+
+        let pivot = candidates
+            .iter()
+            .chain(excluded.iter())
+            .max_by_key(|&u| candidates.intersection_len(&self.adjacency[u]))
+            .unwrap();
+
+        let branches: Vec<usize> = candidates
+            .difference(&self.adjacency[pivot])
+            .iter()
+            .collect();
+        for next in branches {
+            let neighbors = &self.adjacency[next];
+            clique.push(next);
+            self.expand(
+                clique,
+                candidates.intersection(neighbors),
+                excluded.intersection(neighbors),
+                visit,
+            );
+            clique.pop();
+            candidates.remove(next);
+            excluded.insert(next);
+        }
+    }
+
+    fn names_of(&self, clique: &[usize]) -> Vec<Node> {
+        let mut names: Vec<Node> = clique.iter().map(|&id| self.names[id].clone()).collect();
+        names.sort();
+        names
+    }
+}
+
+impl Graph {
+    pub fn maximal_cliques(&self) -> Vec<Vec<Node>> {
+        let graph = IndexedGraph::new(self);
+        let mut cliques = Vec::new();
+        graph.for_each_maximal_clique(|clique| cliques.push(graph.names_of(clique)));
+        cliques.sort();
+        cliques
+    }
+
+    pub fn maximum_clique(&self) -> Vec<Node> {
+        let graph = IndexedGraph::new(self);
+        let mut best: Vec<Node> = Vec::new();
+        graph.for_each_maximal_clique(|clique| {
+            if clique.len() < best.len() {
+                return;
+            }
+            let names = graph.names_of(clique);
+            if names.len() > best.len() || names < best {
+                best = names;
+            }
+        });
+        best
+    }
+
+    pub fn clique_size_histogram(&self) -> BTreeMap<usize, usize> {
+        let graph = IndexedGraph::new(self);
+        let mut histogram = BTreeMap::new();
+        graph.for_each_maximal_clique(|clique| {
+            *histogram.entry(clique.len()).or_default() += 1;
+        });
+        histogram
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::day23::SAMPLE;
+
+    #[test]
+    fn maximum_clique() -> Result<()> {
+        let graph: Graph = SAMPLE.parse()?;
+        assert_eq!(graph.maximum_clique(), ["co", "de", "ka", "ta"]);
+        Ok(())
+    }
+
+    #[test]
+    fn maximal_cliques() -> Result<()> {
+        let graph: Graph = SAMPLE.parse()?;
+        let cliques = graph.maximal_cliques();
+        let indexed = IndexedGraph::new(&graph);
+        for clique in &cliques {
+            let ids: Vec<usize> = clique.iter().map(|n| indexed.id(n).unwrap()).collect();
+            for (k, &a) in ids.iter().enumerate() {
+                assert!(ids[k + 1..].iter().all(|&b| indexed.is_adjacent(a, b)));
+            }
+            let extensible = (0..indexed.len())
+                .any(|c| !ids.contains(&c) && ids.iter().all(|&a| indexed.is_adjacent(a, c)));
+            assert!(!extensible, "{:?} is not maximal", clique);
+        }
+        let histogram = graph.clique_size_histogram();
+        assert_eq!(histogram.values().sum::<usize>(), cliques.len());
+        assert_eq!(histogram.last_key_value(), Some((&4, &1)));
+        Ok(())
+    }
+}
@@ -0,0 +1,207 @@
+use anyhow::Result;
+use regex::Regex;
+
+use super::{
+    clique::{BitSet, IndexedGraph},
+    Graph, Node,
+};
+
+#[derive(Clone, Debug)]
+pub enum NamePattern {
+    Any,
+    Prefix(String),
+    Regex(Regex),
+}
+
+impl NamePattern {
+    pub fn prefix(prefix: &str) -> Self {
+        NamePattern::Prefix(prefix.to_string())
+    }
+
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Ok(NamePattern::Regex(Regex::new(pattern)?))
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        match self {
+            NamePattern::Any => true,
+            NamePattern::Prefix(prefix) => name.starts_with(prefix.as_str()),
+            NamePattern::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Quantifier {
+    AtLeastOne,
+    All,
+}
+
+#[derive(Clone, Debug)]
+pub struct CliqueFilter {
+    pub pattern: NamePattern,
+    pub quantifier: Quantifier,
+}
+
+impl CliqueFilter {
+    pub fn new(pattern: NamePattern, quantifier: Quantifier) -> Self {
+        CliqueFilter {
+            pattern,
+            quantifier,
+        }
+    }
+
+    pub fn any() -> Self {
+        CliqueFilter::new(NamePattern::Any, Quantifier::All)
+    }
+
+    pub fn matches<S: AsRef<str>>(&self, clique: &[S]) -> bool {
+        match self.quantifier {
+            Quantifier::AtLeastOne => clique.iter().any(|n| self.pattern.matches(n.as_ref())),
+            Quantifier::All => clique.iter().all(|n| self.pattern.matches(n.as_ref())),
+        }
+    }
+}
+
+impl IndexedGraph {
+    // Neighbors with larger ids, so that each clique is visited once in
+    // ascending id order.
+    pub fn forward_neighbors(&self) -> Vec<BitSet> {
+        (0..self.len())
+            .map(|id| {
+                let mut forward = BitSet::new(self.len());
+                for other in self.neighbors(id).iter().filter(|&other| other > id) {
+                    forward.insert(other);
+                }
+                forward
+            })
+            .collect()
+    }
+
+    pub fn matching_nodes(&self, pattern: &NamePattern) -> BitSet {
+        let mut nodes = BitSet::new(self.len());
+        for id in (0..self.len()).filter(|&id| pattern.matches(self.name(id))) {
+            nodes.insert(id);
+        }
+        nodes
+    }
+
+    pub fn count_k_cliques(&self, k: usize, allowed: &BitSet) -> usize {
+        if k == 0 {
+            return 1;
+        }
+        let forward = self.forward_neighbors();
+        count_extensions(&forward, k, allowed)
+    }
+
+    pub fn for_each_k_clique(&self, k: usize, allowed: &BitSet, mut visit: impl FnMut(&[usize])) {
+        if k == 0 {
+            visit(&[]);
+            return;
+        }
+        let forward = self.forward_neighbors();
+        let mut clique = Vec::with_capacity(k);
+        visit_extensions(&forward, &mut clique, k, allowed, &mut visit);
+    }
+}
+
+fn count_extensions(forward: &[BitSet], k: usize, candidates: &BitSet) -> usize {
+    if k == 1 {
+        return candidates.len();
+    }
+    candidates
+        .iter()
+        .map(|id| count_extensions(forward, k - 1, &candidates.intersection(&forward[id])))
+        .sum()
+}
+
+fn visit_extensions(
+    forward: &[BitSet],
+    clique: &mut Vec<usize>,
+    k: usize,
+    candidates: &BitSet,
+    visit: &mut impl FnMut(&[usize]),
+) {
+    for id in candidates.iter() {
+        clique.push(id);
+        if k == 1 {
+            visit(clique);
+        } else {
+            visit_extensions(
+                forward,
+                clique,
+                k - 1,
+                &candidates.intersection(&forward[id]),
+                visit,
+            );
+        }
+        clique.pop();
+    }
+}
+
+impl Graph {
+    pub fn count_k_cliques(&self, k: usize, filter: &CliqueFilter) -> usize {
+        let graph = IndexedGraph::new(self);
+        let matching = graph.matching_nodes(&filter.pattern);
+        match filter.quantifier {
+            Quantifier::All => graph.count_k_cliques(k, &matching),
+            Quantifier::AtLeastOne => {
+                let mut all = BitSet::new(graph.len());
+                for id in 0..graph.len() {
+                    all.insert(id);
+                }
+                let unmatched = all.difference(&matching);
+                graph.count_k_cliques(k, &all) - graph.count_k_cliques(k, &unmatched)
+            }
+        }
+    }
+
+    pub fn k_cliques(&self, k: usize, filter: &CliqueFilter) -> Vec<Vec<Node>> {
+        let graph = IndexedGraph::new(self);
+        let allowed = match filter.quantifier {
+            Quantifier::All => graph.matching_nodes(&filter.pattern),
+            Quantifier::AtLeastOne => graph.matching_nodes(&NamePattern::Any),
+        };
+        let mut cliques = Vec::new();
+        graph.for_each_k_clique(k, &allowed, |clique| {
+            let names: Vec<Node> = clique
+                .iter()
+                .map(|&id| graph.name(id).to_string())
+                .collect();
+            if filter.matches(&names) {
+                cliques.push(names);
+            }
+        });
+        cliques
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::day23::SAMPLE;
+
+    #[test]
+    fn triangles() -> Result<()> {
+        let graph: Graph = SAMPLE.parse()?;
+        assert_eq!(graph.count_k_cliques(3, &CliqueFilter::any()), 12);
+
+        let filter = CliqueFilter::new(NamePattern::prefix("t"), Quantifier::AtLeastOne);
+        assert_eq!(graph.count_k_cliques(3, &filter), 7);
+        let cliques = graph.k_cliques(3, &filter);
+        assert_eq!(cliques.len(), 7);
+        assert_eq!(cliques[0], ["co", "de", "ta"]);
+        Ok(())
+    }
+
+    #[test]
+    fn filters() -> Result<()> {
+        let graph: Graph = SAMPLE.parse()?;
+        assert_eq!(graph.count_k_cliques(4, &CliqueFilter::any()), 1);
+
+        let filter = CliqueFilter::new(NamePattern::regex("^[ckt]")?, Quantifier::All);
+        assert_eq!(graph.k_cliques(3, &filter), [["co", "ka", "ta"]]);
+        assert_eq!(graph.count_k_cliques(3, &filter), 1);
+        Ok(())
+    }
+}
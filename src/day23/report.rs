@@ -0,0 +1,349 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    fmt::{Display, Write},
+};
+
+use super::{
+    clique::{BitSet, IndexedGraph},
+    Graph, Node,
+};
+
+impl IndexedGraph {
+    pub fn components(&self) -> Vec<Vec<usize>> {
+        let mut seen = BitSet::new(self.len());
+        let mut components = Vec::new();
+        for root in 0..self.len() {
+            if seen.contains(root) {
+                continue;
+            }
+            seen.insert(root);
+            let mut component = Vec::new();
+            let mut queue = VecDeque::from([root]);
+            while let Some(id) = queue.pop_front() {
+                component.push(id);
+                for next in self.neighbors(id).difference(&seen).iter() {
+                    seen.insert(next);
+                    queue.push_back(next);
+                }
+            }
+            component.sort();
+            components.push(component);
+        }
+        components
+    }
+
+    pub fn clustering_coefficient(&self, id: usize) -> f64 {
+        let neighbors = self.neighbors(id);
+        let degree = neighbors.len();
+        if degree < 2 {
+            return 0.0;
+        }
+        let links: usize = neighbors
+            .iter()
+            .map(|other| self.neighbors(other).intersection_len(neighbors))
+            .sum::<usize>()
+            / 2;
+        links as f64 / (degree * (degree - 1) / 2) as f64
+    }
+
+    pub fn core_numbers(&self) -> Vec<usize> {
+        let mut degrees: Vec<usize> = (0..self.len()).map(|id| self.neighbors(id).len()).collect();
+        let mut queue: BTreeSet<(usize, usize)> = degrees
+            .iter()
+            .enumerate()
+            .map(|(id, &degree)| (degree, id))
+            .collect();
+        let mut removed = BitSet::new(self.len());
+        let mut cores = vec![0; self.len()];
+        let mut core = 0;
+        while let Some((degree, id)) = queue.pop_first() {
+            core = core.max(degree);
+            cores[id] = core;
+            removed.insert(id);
+            for other in self.neighbors(id).difference(&removed).iter() {
+                queue.remove(&(degrees[other], other));
+                degrees[other] -= 1;
+                queue.insert((degrees[other], other));
+            }
+        }
+        cores
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Report {
+    pub degree_histogram: BTreeMap<usize, usize>,
+    pub components: Vec<Vec<Node>>,
+    pub clustering: BTreeMap<Node, f64>,
+    pub core_numbers: BTreeMap<Node, usize>,
+    pub largest_clique: Vec<Node>,
+}
+
+impl Report {
+    pub fn new(graph: &Graph) -> Self {
+        let indexed = IndexedGraph::new(graph);
+        let names = |ids: &[usize]| -> Vec<Node> {
+            ids.iter().map(|&id| indexed.name(id).to_string()).collect()
+        };
+
+        let mut degree_histogram = BTreeMap::new();
+        for id in 0..indexed.len() {
+            *degree_histogram
+                .entry(indexed.neighbors(id).len())
+                .or_default() += 1;
+        }
+        let mut components: Vec<Vec<Node>> = indexed
+            .components()
+            .iter()
+            .map(|component| names(component))
+            .collect();
+        components.sort_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)));
+        let clustering = (0..indexed.len())
+            .map(|id| {
+                (
+                    indexed.name(id).to_string(),
+                    indexed.clustering_coefficient(id),
+                )
+            })
+            .collect();
+        let core_numbers = indexed
+            .core_numbers()
+            .into_iter()
+            .enumerate()
+            .map(|(id, core)| (indexed.name(id).to_string(), core))
+            .collect();
+
+        Report {
+            degree_histogram,
+            components,
+            clustering,
+            core_numbers,
+            largest_clique: graph.maximum_clique(),
+        }
+    }
+
+    pub fn average_clustering(&self) -> f64 {
+        if self.clustering.is_empty() {
+            return 0.0;
+        }
+        self.clustering.values().sum::<f64>() / self.clustering.len() as f64
+    }
+
+    pub fn degeneracy(&self) -> usize {
+        self.core_numbers.values().copied().max().unwrap_or(0)
+    }
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Degree distribution:")?;
+        for (degree, count) in &self.degree_histogram {
+            writeln!(f, "  {:>3}: {}", degree, count)?;
+        }
+        writeln!(f, "Connected components: {}", self.components.len())?;
+        for component in &self.components {
+            writeln!(f, "  {} nodes: {}", component.len(), component.join(","))?;
+        }
+        writeln!(
+            f,
+            "Average clustering coefficient: {:.4}",
+            self.average_clustering()
+        )?;
+        for (node, coefficient) in &self.clustering {
+            writeln!(f, "  {}: {:.4}", node, coefficient)?;
+        }
+        writeln!(f, "Degeneracy: {}", self.degeneracy())?;
+        let mut shells: BTreeMap<usize, Vec<&str>> = BTreeMap::new();
+        for (node, &core) in &self.core_numbers {
+            shells.entry(core).or_default().push(node);
+        }
+        for (core, nodes) in &shells {
+            writeln!(f, "  {}-shell: {}", core, nodes.join(","))?;
+        }
+        writeln!(
+            f,
+            "Largest clique ({} nodes): {}",
+            self.largest_clique.len(),
+            self.largest_clique.join(",")
+        )
+    }
+}
+
+fn sorted_edges(graph: &Graph) -> Vec<(&str, &str)> {
+    let mut edges: Vec<(&str, &str)> = graph
+        .edges
+        .iter()
+        .flat_map(|(a, bs)| {
+            bs.iter()
+                .filter(move |b| a < *b)
+                .map(move |b| (a.as_str(), b.as_str()))
+        })
+        .collect();
+    edges.sort();
+    edges
+}
+
+// Quotes a JSON string.
+fn quote(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            _ if (c as u32) < 0x20 => {
+                write!(quoted, "\\u{:04x}", c as u32).unwrap();
+            }
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+// Quotes a DOT string. DOT has no escapes for control characters, which
+// are kept as they are.
+fn quote_dot(s: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in s.chars() {
+        if matches!(c, '"' | '\\') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl Graph {
+    pub fn to_dot(&self, highlight: &[Node]) -> String {
+        let highlight: BTreeSet<&str> = highlight.iter().map(|n| n.as_str()).collect();
+        let mut nodes: Vec<&str> = self.edges.keys().map(|n| n.as_str()).collect();
+        nodes.sort();
+
+        let mut dot = String::from("graph lan {\n");
+        dot.push_str("    node [shape=circle];\n");
+        for node in nodes {
+            if highlight.contains(node) {
+                writeln!(
+                    dot,
+                    "    {} [style=filled, fillcolor=red, fontcolor=white];",
+                    quote_dot(node)
+                )
+                .unwrap();
+            } else {
+                writeln!(dot, "    {};", quote_dot(node)).unwrap();
+            }
+        }
+        for (a, b) in sorted_edges(self) {
+            if highlight.contains(a) && highlight.contains(b) {
+                writeln!(
+                    dot,
+                    "    {} -- {} [color=red, penwidth=3];",
+                    quote_dot(a),
+                    quote_dot(b)
+                )
+                .unwrap();
+            } else {
+                writeln!(
+                    dot,
+                    "    {} -- {} [color=gray];",
+                    quote_dot(a),
+                    quote_dot(b)
+                )
+                .unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_json(&self) -> String {
+        let adjacency: BTreeMap<&str, BTreeSet<&str>> = self
+            .edges
+            .iter()
+            .map(|(a, bs)| (a.as_str(), bs.iter().map(|b| b.as_str()).collect()))
+            .collect();
+
+        let mut json = String::from("{\n  \"edges\": [");
+        for (k, (a, b)) in sorted_edges(self).into_iter().enumerate() {
+            if k > 0 {
+                json.push(',');
+            }
+            write!(json, "\n    [{}, {}]", quote(a), quote(b)).unwrap();
+        }
+        json.push_str("\n  ],\n  \"adjacency\": {");
+        for (k, (a, bs)) in adjacency.into_iter().enumerate() {
+            if k > 0 {
+                json.push(',');
+            }
+            let bs: Vec<String> = bs.into_iter().map(quote).collect();
+            write!(json, "\n    {}: [{}]", quote(a), bs.join(", ")).unwrap();
+        }
+        json.push_str("\n  }\n}\n");
+        json
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::Result;
+
+    use super::*;
+    use crate::day23::SAMPLE;
+
+    #[test]
+    fn report() -> Result<()> {
+        let graph: Graph = SAMPLE.parse()?;
+        let report = Report::new(&graph);
+        assert_eq!(report.degree_histogram, BTreeMap::from([(4, 16)]));
+        assert_eq!(report.components.len(), 1);
+        assert_eq!(report.clustering["co"], 0.5);
+        assert_eq!(report.degeneracy(), 4);
+        assert_eq!(report.largest_clique, ["co", "de", "ka", "ta"]);
+        Ok(())
+    }
+
+    #[test]
+    fn quoting() {
+        assert_eq!(quote("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(quote("x\ny\tz\u{1}"), r#""x\ny\tz\u0001""#);
+        assert_eq!(quote_dot("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(quote_dot("x\ty\u{1}"), "\"x\ty\u{1}\"");
+    }
+
+    #[test]
+    fn export() -> Result<()> {
+        let graph: Graph = "a-b\nb-c\n".parse()?;
+        assert_eq!(
+            graph.to_dot(&["a".to_string(), "b".to_string()]),
+            r#"graph lan {
+    node [shape=circle];
+    "a" [style=filled, fillcolor=red, fontcolor=white];
+    "b" [style=filled, fillcolor=red, fontcolor=white];
+    "c";
+    "a" -- "b" [color=red, penwidth=3];
+    "b" -- "c" [color=gray];
+}
+"#
+        );
+        assert_eq!(
+            graph.to_json(),
+            r#"{
+  "edges": [
+    ["a", "b"],
+    ["b", "c"]
+  ],
+  "adjacency": {
+    "a": ["b"],
+    "b": ["a", "c"],
+    "c": ["b"]
+  }
+}
+"#
+        );
+        Ok(())
+    }
+}
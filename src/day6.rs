@@ -0,0 +1,425 @@
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+
+use crate::parallel;
+
+pub type Position = (isize, isize);
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
+pub enum Direction {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Direction {
+    pub fn delta(self) -> Position {
+        match self {
+            Direction::Up => (-1, 0),
+            Direction::Down => (1, 0),
+            Direction::Left => (0, -1),
+            Direction::Right => (0, 1),
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Self> {
+        match c {
+            '^' => Some(Direction::Up),
+            'v' => Some(Direction::Down),
+            '<' => Some(Direction::Left),
+            '>' => Some(Direction::Right),
+            _ => None,
+        }
+    }
+
+    pub fn turn_right(self) -> Self {
+        match self {
+            Direction::Up => Direction::Right,
+            Direction::Down => Direction::Left,
+            Direction::Left => Direction::Up,
+            Direction::Right => Direction::Down,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
+pub struct GuardState {
+    pub pos: Position,
+    pub dir: Direction,
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub size: (isize, isize),
+    pub blocks: BTreeSet<Position>,
+    pub guard: GuardState,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let mut blocks: BTreeSet<Position> = BTreeSet::new();
+        let mut guard_pos: Option<Position> = None;
+        let mut guard_dir: Option<Direction> = None;
+
+        let lines: Vec<&str> = input.trim().lines().collect();
+        let n = lines.len() as isize;
+        let m = lines[0].len() as isize;
+
+        for (i, line) in lines.iter().enumerate() {
+            let chars: Vec<char> = line.chars().collect();
+            ensure!(chars.len() == m as usize, "Invalid dimension");
+            for (j, c) in chars.into_iter().enumerate() {
+                if c == '#' {
+                    blocks.insert((i as isize, j as isize));
+                }
+                if let Some(dir) = Direction::from_char(c) {
+                    ensure!(guard_pos.is_none(), "Multiple guards");
+                    ensure!(guard_dir.is_none(), "Multiple guards");
+                    guard_pos = Some((i as isize, j as isize));
+                    guard_dir = Some(dir);
+                }
+            }
+        }
+
+        let guard_pos = guard_pos.context("No guard")?;
+        let guard_dir = guard_dir.context("No guard")?;
+
+        Ok(Problem {
+            size: (n, m),
+            blocks,
+            guard: GuardState {
+                pos: guard_pos,
+                dir: guard_dir,
+            },
+        })
+    }
+}
+
+// Sorted obstacle columns of each row and obstacle rows of each column, so
+// the guard can jump straight to the next obstacle ahead.
+#[derive(Clone, Debug)]
+pub struct JumpTable {
+    pub size: (isize, isize),
+    pub rows: Vec<Vec<isize>>,
+    pub cols: Vec<Vec<isize>>,
+}
+
+impl JumpTable {
+    pub fn new(problem: &Problem) -> Self {
+        let mut rows = vec![Vec::new(); problem.size.0 as usize];
+        let mut cols = vec![Vec::new(); problem.size.1 as usize];
+        // Blocks are sorted by row and then column, so both lists come out
+        // sorted.
+        for &(i, j) in &problem.blocks {
+            rows[i as usize].push(j);
+            cols[j as usize].push(i);
+        }
+        JumpTable {
+            size: problem.size,
+            rows,
+            cols,
+        }
+    }
+
+    pub fn contains(&self, pos: Position) -> bool {
+        self.in_bound(pos) && self.rows[pos.0 as usize].binary_search(&pos.1).is_ok()
+    }
+
+    pub fn in_bound(&self, pos: Position) -> bool {
+        pos.0 >= 0 && pos.0 < self.size.0 && pos.1 >= 0 && pos.1 < self.size.1
+    }
+
+    // Where the guard stops and turns next, or None if it walks off the map.
+    // The extra obstacle is overlaid on the table without modifying it.
+    pub fn next_turn(&self, state: GuardState, extra: Option<Position>) -> Option<GuardState> {
+        let (i, j) = state.pos;
+        // Coordinate of the nearest obstacle ahead along the line of travel.
+        let ahead = |line: &[isize], at: isize, forward: bool| -> Option<isize> {
+            if forward {
+                let k = line.partition_point(|&x| x <= at);
+                line.get(k).copied()
+            } else {
+                let k = line.partition_point(|&x| x < at);
+                k.checked_sub(1).map(|k| line[k])
+            }
+        };
+        let (stop, forward) = match state.dir {
+            Direction::Up => (ahead(&self.cols[j as usize], i, false), false),
+            Direction::Down => (ahead(&self.cols[j as usize], i, true), true),
+            Direction::Left => (ahead(&self.rows[i as usize], j, false), false),
+            Direction::Right => (ahead(&self.rows[i as usize], j, true), true),
+        };
+        let vertical = matches!(state.dir, Direction::Up | Direction::Down);
+        let extra = extra.and_then(|(ei, ej)| match vertical {
+            true if ej == j && (ei > i) == forward && ei != i => Some(ei),
+            false if ei == i && (ej > j) == forward && ej != j => Some(ej),
+            _ => None,
+        });
+        let stop = match (stop, extra) {
+            (Some(a), Some(b)) => Some(if forward { a.min(b) } else { a.max(b) }),
+            (a, b) => a.or(b),
+        }?;
+        let (di, dj) = state.dir.delta();
+        let pos = if vertical {
+            (stop - di, j)
+        } else {
+            (i, stop - dj)
+        };
+        Some(GuardState {
+            pos,
+            dir: state.dir.turn_right(),
+        })
+    }
+
+    // Whether the guard starting from the state walks in circles forever.
+    // Only states right after a turn are remembered.
+    pub fn loops(&self, start: GuardState, extra: Option<Position>) -> bool {
+        let mut turns: HashSet<GuardState> = HashSet::new();
+        let mut state = start;
+        while let Some(next) = self.next_turn(state, extra) {
+            if !turns.insert(next) {
+                return true;
+            }
+            state = next;
+        }
+        false
+    }
+}
+
+// Cells the guard visits in order, each with the state the guard was in
+// just before first stepping onto it. The starting cell comes first. A
+// guard walking in circles has no such path, which is an error.
+pub fn path(problem: &Problem, table: &JumpTable) -> Result<Vec<(Position, GuardState)>> {
+    ensure!(!table.loops(problem.guard, None), "Guard never leaves");
+    let mut visited: HashSet<Position> = HashSet::from([problem.guard.pos]);
+    let mut path = vec![(problem.guard.pos, problem.guard)];
+    let mut state = problem.guard;
+    loop {
+        let (di, dj) = state.dir.delta();
+        let next = (state.pos.0 + di, state.pos.1 + dj);
+        if !table.in_bound(next) {
+            break;
+        }
+        if table.contains(next) {
+            state.dir = state.dir.turn_right();
+            continue;
+        }
+        if visited.insert(next) {
+            path.push((next, state));
+        }
+        state.pos = next;
+    }
+    Ok(path)
+}
+
+// Obstacle positions that trap the guard in a loop. Only cells on the
+// original path can change the guard's route, and the guard follows the
+// original route until it first runs into the new obstacle, so each check
+// starts from there. A guard that already loops stays trapped by any
+// obstacle off its route, so then every free cell is checked from the start.
+pub fn loop_obstacles(problem: &Problem) -> Result<Vec<Position>> {
+    let table = JumpTable::new(problem);
+    let candidates = if table.loops(problem.guard, None) {
+        (0..problem.size.0)
+            .flat_map(|i| (0..problem.size.1).map(move |j| (i, j)))
+            .filter(|pos| !problem.blocks.contains(pos) && *pos != problem.guard.pos)
+            .map(|pos| (pos, problem.guard))
+            .collect()
+    } else {
+        path(problem, &table)?.split_off(1)
+    };
+    let mut obstacles = parallel::map_chunks(&candidates, |chunk| {
+        chunk
+            .iter()
+            .filter(|&&(pos, before)| table.loops(before, Some(pos)))
+            .map(|&(pos, _)| pos)
+            .collect::<Vec<_>>()
+    })
+    .concat();
+    obstacles.sort();
+    Ok(obstacles)
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Visited,
+    Loops,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub mode: Mode,
+}
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        for arg in args {
+            match arg.as_str() {
+                "--visited" => self.mode = Mode::Visited,
+                "--loops" => self.mode = Mode::Loops,
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<usize> {
+    Ok(match options.mode {
+        Mode::Visited => path(problem, &JumpTable::new(problem))?.len(),
+        Mode::Loops => loop_obstacles(problem)?.len(),
+    })
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let mut output = String::new();
+    writeln!(output, "{}", solve(problem, options)?)?;
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Lcg;
+
+    const SAMPLE: &str = "....#.....
+.........#
+..........
+..#.......
+.......#..
+..........
+.#..^.....
+........#.
+#.........
+......#...
+";
+
+    #[test]
+    fn obstacles() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        assert_eq!(
+            loop_obstacles(&problem)?,
+            [(6, 3), (7, 6), (7, 7), (8, 1), (8, 3), (9, 7)]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn jumps() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        let table = JumpTable::new(&problem);
+        let turn = table.next_turn(problem.guard, None).unwrap();
+        assert_eq!(
+            turn,
+            GuardState {
+                pos: (1, 4),
+                dir: Direction::Right
+            }
+        );
+        // An overlaid obstacle closer than the real one stops the guard first.
+        let turn = table.next_turn(turn, Some((1, 6))).unwrap();
+        assert_eq!(turn.pos, (1, 5));
+        // One behind the guard is ignored.
+        assert_eq!(
+            table.next_turn(problem.guard, Some((7, 4))),
+            table.next_turn(problem.guard, None)
+        );
+        let exit = GuardState {
+            pos: (0, 0),
+            dir: Direction::Left,
+        };
+        assert_eq!(table.next_turn(exit, None), None);
+        Ok(())
+    }
+
+    #[test]
+    fn already_loops() -> Result<()> {
+        let problem: Problem = ".#..\n...#\n#^..\n..#.\n".parse()?;
+        // There is no path to count, but any obstacle off the guard's route
+        // keeps it trapped, while each of the other three route cells lets
+        // it out.
+        let visited = Options {
+            mode: Mode::Visited,
+        };
+        assert!(solve(&problem, &visited).is_err());
+        let loops = Options { mode: Mode::Loops };
+        assert_eq!(solve(&problem, &loops)?, 8);
+        let obstacles = loop_obstacles(&problem)?;
+        assert!([(1, 1), (1, 2), (2, 2)]
+            .iter()
+            .all(|pos| !obstacles.contains(pos)));
+        Ok(())
+    }
+
+    // The original one-step-at-a-time loop check on a modified copy.
+    fn loops_naive(problem: &Problem, obstacle: Position) -> bool {
+        let mut blocks = problem.blocks.clone();
+        blocks.insert(obstacle);
+        let mut state = problem.guard;
+        let mut visited: BTreeSet<GuardState> = BTreeSet::new();
+        while visited.insert(state) {
+            let (di, dj) = state.dir.delta();
+            let next = (state.pos.0 + di, state.pos.1 + dj);
+            if blocks.contains(&next) {
+                state.dir = state.dir.turn_right();
+            } else if next.0 < 0
+                || next.0 >= problem.size.0
+                || next.1 < 0
+                || next.1 >= problem.size.1
+            {
+                return false;
+            } else {
+                state.pos = next;
+            }
+        }
+        true
+    }
+
+    fn random_map(size: usize, seed: u64) -> String {
+        let mut rng = Lcg(seed);
+        let mut map = String::new();
+        for i in 0..size {
+            for j in 0..size {
+                let roll = rng.next(8);
+                map.push(if (i, j) == (size / 2, size / 2) {
+                    '^'
+                } else if roll == 0 {
+                    '#'
+                } else {
+                    '.'
+                });
+            }
+            map.push('\n');
+        }
+        map
+    }
+
+    #[test]
+    fn matches_naive() -> Result<()> {
+        for seed in 0..20 {
+            let problem: Problem = random_map(24, seed).parse()?;
+            let mut expected = Vec::new();
+            for i in 0..problem.size.0 {
+                for j in 0..problem.size.1 {
+                    let pos = (i, j);
+                    if !problem.blocks.contains(&pos)
+                        && pos != problem.guard.pos
+                        && loops_naive(&problem, pos)
+                    {
+                        expected.push(pos);
+                    }
+                }
+            }
+            assert_eq!(loop_obstacles(&problem)?, expected, "seed {}", seed);
+        }
+        Ok(())
+    }
+}
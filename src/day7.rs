@@ -0,0 +1,419 @@
+use std::{borrow::Cow, collections::HashMap, fmt, fmt::Write, str::FromStr};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+
+pub mod operators;
+
+use crate::parallel;
+use operators::Operators;
+
+#[derive(Clone, Debug)]
+pub struct Equation {
+    pub target: u128,
+    pub factors: Vec<u128>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub equations: Vec<Equation>,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let equations = input
+            .trim()
+            .lines()
+            .map(|line| {
+                let (target_str, factors_str) = line.split_once(": ").context("Invalid input")?;
+                let target = target_str.parse()?;
+                let factors = factors_str
+                    .split_ascii_whitespace()
+                    .map(|x| x.parse::<u128>())
+                    .collect::<Result<Vec<_>, _>>()?;
+                ensure!(!factors.is_empty(), "No factors: {}", line);
+                Ok(Equation { target, factors })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Problem { equations })
+    }
+}
+
+// An equation with the operators that make it true, e.g.
+// "6 * 8 || 6 * 15 = 7290".
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Expression {
+    pub target: u128,
+    pub factors: Vec<u128>,
+    pub symbols: Vec<String>,
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.factors[0])?;
+        for (symbol, factor) in self.symbols.iter().zip(&self.factors[1..]) {
+            write!(f, " {} {}", symbol, factor)?;
+        }
+        write!(f, " = {}", self.target)
+    }
+}
+
+// Works from the target back to the first factor, undoing the last
+// operation at each step. Only operations that could have produced the
+// current value are followed, which prunes far more than searching forward.
+// When an operator has too many preimages to list, the factors before it
+// are evaluated forward instead, keeping every value they can take. Both
+// are memoised on the prefix length and value.
+#[derive(Clone, Debug)]
+struct BackwardSearch<'a> {
+    operators: &'a Operators,
+    factors: &'a [u128],
+    found: HashMap<(usize, u128), bool>,
+    counts: HashMap<(usize, u128), u128>,
+    // values[i] maps each value of the first i + 1 factors to the number of
+    // operator assignments giving it.
+    values: Vec<HashMap<u128, u128>>,
+}
+
+impl<'a> BackwardSearch<'a> {
+    fn new(operators: &'a Operators, factors: &'a [u128]) -> Self {
+        BackwardSearch {
+            operators,
+            factors,
+            found: HashMap::new(),
+            counts: HashMap::new(),
+            values: vec![[(factors[0], 1)].into()],
+        }
+    }
+
+    fn values(&mut self, len: usize) -> &HashMap<u128, u128> {
+        while self.values.len() < len {
+            let b = self.factors[self.values.len()];
+            let mut next: HashMap<u128, u128> = HashMap::new();
+            for (&a, &ways) in self.values.last().unwrap() {
+                for op in self.operators.iter() {
+                    if let Some(value) = op.apply(a, b) {
+                        *next.entry(value).or_default() += ways;
+                    }
+                }
+            }
+            self.values.push(next);
+        }
+        &self.values[len - 1]
+    }
+
+    // Whether the first len factors can produce target.
+    fn find(&mut self, target: u128, len: usize) -> bool {
+        if len == 1 {
+            return target == self.factors[0];
+        }
+        if let Some(&found) = self.found.get(&(len, target)) {
+            return found;
+        }
+        let last = self.factors[len - 1];
+        let operators = self.operators;
+        let found = operators.iter().any(|op| match op.undo(target, last) {
+            Some(previous) => previous.into_iter().any(|a| self.find(a, len - 1)),
+            None => self
+                .values(len - 1)
+                .keys()
+                .any(|&a| op.apply(a, last) == Some(target)),
+        });
+        self.found.insert((len, target), found);
+        found
+    }
+
+    fn count(&mut self, target: u128, len: usize) -> u128 {
+        if len == 1 {
+            return (target == self.factors[0]) as u128;
+        }
+        if let Some(&count) = self.counts.get(&(len, target)) {
+            return count;
+        }
+        let last = self.factors[len - 1];
+        let operators = self.operators;
+        let count = operators
+            .iter()
+            .map(|op| match op.undo(target, last) {
+                Some(previous) => previous.into_iter().map(|a| self.count(a, len - 1)).sum(),
+                None => self
+                    .values(len - 1)
+                    .iter()
+                    .filter(|&(&a, _)| op.apply(a, last) == Some(target))
+                    .map(|(_, &ways)| ways)
+                    .sum::<u128>(),
+            })
+            .sum();
+        self.counts.insert((len, target), count);
+        count
+    }
+}
+
+fn solvable(equation: &Equation, operators: &Operators) -> bool {
+    BackwardSearch::new(operators, &equation.factors).find(equation.target, equation.factors.len())
+}
+
+// The operators are picked from the front, each time taking the first one
+// that leaves the rest solvable, so this returns the same expression as a
+// forward search.
+pub fn can_produce(equation: &Equation, operators: &Operators) -> Option<Expression> {
+    if !solvable(equation, operators) {
+        return None;
+    }
+    let mut rest = equation.clone();
+    let mut symbols = Vec::new();
+    for (i, &b) in equation.factors.iter().enumerate().skip(1) {
+        let (symbol, next) = operators.iter().find_map(|op| {
+            let value = op.apply(rest.factors[0], b)?;
+            let next = Equation {
+                target: equation.target,
+                factors: [value]
+                    .into_iter()
+                    .chain(equation.factors[i + 1..].iter().copied())
+                    .collect(),
+            };
+            solvable(&next, operators).then(|| (op.symbol(), next))
+        })?;
+        symbols.push(symbol);
+        rest = next;
+    }
+    Some(Expression {
+        target: equation.target,
+        factors: equation.factors.clone(),
+        symbols,
+    })
+}
+
+// Number of operator assignments that make the equation true.
+pub fn count_solutions(equation: &Equation, operators: &Operators) -> u128 {
+    BackwardSearch::new(operators, &equation.factors).count(equation.target, equation.factors.len())
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Sum,
+    Expressions,
+    Counts,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub operators: Cow<'static, str>,
+    pub mode: Mode,
+}
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--operators" => {
+                    let operators = args.next().context("Missing --operators value")?;
+                    self.operators = Cow::Owned(operators);
+                }
+                "--expressions" => self.mode = Mode::Expressions,
+                "--counts" => self.mode = Mode::Counts,
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<u128> {
+    let operators: Operators = options.operators.parse()?;
+    let solvable = parallel::map(&problem.equations, |equation| {
+        can_produce(equation, &operators).is_some()
+    });
+    problem
+        .equations
+        .iter()
+        .zip(solvable)
+        .filter(|(_, solvable)| *solvable)
+        .try_fold(0u128, |sum, (equation, _)| sum.checked_add(equation.target))
+        .context("Sum overflows")
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let operators: Operators = options.operators.parse()?;
+    let mut output = String::new();
+    match options.mode {
+        Mode::Sum => writeln!(output, "{}", solve(problem, options)?)?,
+        Mode::Expressions => {
+            let expressions = parallel::map(&problem.equations, |equation| {
+                can_produce(equation, &operators)
+            });
+            for expression in expressions.into_iter().flatten() {
+                writeln!(output, "{}", expression)?;
+            }
+        }
+        Mode::Counts => {
+            let counts = parallel::map(&problem.equations, |equation| {
+                count_solutions(equation, &operators)
+            });
+            for (equation, count) in problem.equations.iter().zip(counts) {
+                writeln!(output, "{}: {}", equation.target, count)?;
+            }
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Lcg;
+
+    const SAMPLE: &str = "190: 10 19
+3267: 81 40 27
+83: 17 5
+156: 15 6
+7290: 6 8 6 15
+161011: 16 10 13
+192: 17 8 14
+21037: 9 7 18 13
+292: 11 6 16 20
+";
+
+    fn options(operators: &'static str, mode: Mode) -> Options {
+        Options {
+            operators: Cow::Borrowed(operators),
+            mode,
+        }
+    }
+
+    #[test]
+    fn expressions() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        let output = run(&problem, &options("+ * ||", Mode::Expressions))?;
+        assert_eq!(
+            output,
+            "10 * 19 = 190
+81 + 40 * 27 = 3267
+15 || 6 = 156
+6 * 8 || 6 * 15 = 7290
+17 || 8 + 14 = 192
+11 + 6 * 16 + 20 = 292
+"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn counts() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        let output = run(&problem, &options("+ *", Mode::Counts))?;
+        assert_eq!(
+            output,
+            "190: 1\n3267: 2\n83: 0\n156: 0\n7290: 0\n161011: 0\n192: 0\n21037: 0\n292: 1\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn other_operators() -> Result<()> {
+        let problem: Problem = "4: 10 6\n5: 10 2\n15: 3 3\n8: 2 3\n".parse()?;
+        let output = run(&problem, &options("- / ||2", Mode::Expressions))?;
+        // 0b11 || 0b11 = 0b1111, and 2 - 3 is not allowed.
+        assert_eq!(output, "10 - 6 = 4\n10 / 2 = 5\n3 ||2 3 = 15\n");
+        assert_eq!(solve(&problem, &options("- / ||2", Mode::Sum))?, 24);
+        Ok(())
+    }
+
+    // Builds an equation from random factors and operators, so that it is
+    // always satisfiable. Returns None if the target would overflow.
+    fn random_equation(len: usize, max: u64, seed: u64, operators: &Operators) -> Option<Equation> {
+        let mut rng = Lcg(seed);
+        let factors: Vec<u128> = (0..len).map(|_| rng.next(max) as u128 + 1).collect();
+        let mut target = factors[0];
+        for &b in &factors[1..] {
+            let op = operators.get(rng.next(operators.len() as u64) as usize);
+            target = op.apply(target, b)?;
+        }
+        Some(Equation { target, factors })
+    }
+
+    // Tries every operator assignment.
+    fn naive_count(equation: &Equation, operators: &Operators) -> u128 {
+        let mut values = vec![equation.factors[0]];
+        for &b in &equation.factors[1..] {
+            values = values
+                .iter()
+                .flat_map(|&a| operators.iter().filter_map(move |op| op.apply(a, b)))
+                .collect();
+        }
+        values.iter().filter(|&&v| v == equation.target).count() as u128
+    }
+
+    #[test]
+    fn backward_matches_forward() -> Result<()> {
+        let operators: Operators = "+ * ||".parse()?;
+        for seed in 0..50 {
+            let equation = random_equation(6, 999, seed, &operators).unwrap();
+            let expression = can_produce(&equation, &operators).unwrap();
+            let forward = naive_count(&equation, &operators);
+            assert_eq!(
+                count_solutions(&equation, &operators),
+                forward,
+                "{}",
+                expression
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn long_equations() -> Result<()> {
+        let operators: Operators = "+ * ||".parse()?;
+        let problem = Problem {
+            equations: (0..100)
+                .filter_map(|seed| random_equation(24, 99, seed, &operators))
+                .take(8)
+                .collect(),
+        };
+        assert!(problem
+            .equations
+            .iter()
+            .any(|equation| equation.target > u64::MAX as u128));
+        let expected = problem
+            .equations
+            .iter()
+            .map(|equation| equation.target)
+            .sum();
+        assert_eq!(solve(&problem, &options("+ * ||", Mode::Sum))?, expected);
+
+        // Values past u128 are never produced.
+        let problem: Problem = format!("{}: 9 {}\n", u128::MAX, u128::MAX).parse()?;
+        assert_eq!(solve(&problem, &options("+ * ||", Mode::Sum))?, 0);
+        Ok(())
+    }
+
+    #[test]
+    fn fallback() -> Result<()> {
+        // Division by a large factor cannot be undone cheaply, so this
+        // evaluates the first factor forward.
+        let problem: Problem = "7: 700 100\n".parse()?;
+        let output = run(&problem, &options("/ +", Mode::Counts))?;
+        assert_eq!(output, "7: 1\n");
+
+        // Any value times 0 is 0, so the zeros are evaluated forward.
+        let operators: Operators = "+ * ||".parse()?;
+        for zeros in 0..8 {
+            let equation = Equation {
+                target: 5,
+                factors: [1].into_iter().chain(vec![0; zeros]).chain([5]).collect(),
+            };
+            assert_eq!(
+                count_solutions(&equation, &operators),
+                naive_count(&equation, &operators)
+            );
+        }
+        let problem: Problem = format!("5: 1{} 5\n", " 0".repeat(22)).parse()?;
+        let output = run(&problem, &options("+ * ||", Mode::Counts))?;
+        assert_eq!(output, "5: 62753730611\n");
+        let output = run(&problem, &options("+ * ||", Mode::Expressions))?;
+        assert_eq!(output, format!("1{} * 5 = 5\n", " + 0".repeat(22)));
+        Ok(())
+    }
+}
@@ -0,0 +1,242 @@
+use std::{fmt::Debug, str::FromStr};
+
+use anyhow::{bail, ensure, Error, Result};
+
+// A binary operator applied left to right while evaluating an equation.
+pub trait Operator: Debug + Send + Sync {
+    fn symbol(&self) -> String;
+
+    // Returns None if the result is undefined or does not fit.
+    fn apply(&self, a: u128, b: u128) -> Option<u128>;
+
+    // Every a with apply(a, b) == result, or None if there are too many to
+    // list.
+    fn undo(&self, result: u128, b: u128) -> Option<Vec<u128>>;
+}
+
+// Most values undo may return before giving up.
+const MAX_PREIMAGES: u128 = 64;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Add;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Subtract;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Multiply;
+
+// Integer division rounding down.
+#[derive(Clone, Copy, Debug)]
+pub struct Divide;
+
+// Appends the digits of b to a, written in the given base.
+#[derive(Clone, Copy, Debug)]
+pub struct Concat {
+    pub base: u128,
+}
+
+impl Operator for Add {
+    fn symbol(&self) -> String {
+        "+".into()
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_add(b)
+    }
+
+    fn undo(&self, result: u128, b: u128) -> Option<Vec<u128>> {
+        Some(result.checked_sub(b).into_iter().collect())
+    }
+}
+
+impl Operator for Subtract {
+    fn symbol(&self) -> String {
+        "-".into()
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_sub(b)
+    }
+
+    fn undo(&self, result: u128, b: u128) -> Option<Vec<u128>> {
+        Some(result.checked_add(b).into_iter().collect())
+    }
+}
+
+impl Operator for Multiply {
+    fn symbol(&self) -> String {
+        "*".into()
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_mul(b)
+    }
+
+    fn undo(&self, result: u128, b: u128) -> Option<Vec<u128>> {
+        match (result, b) {
+            (0, 0) => None,
+            (_, 0) => Some(vec![]),
+            _ if result.is_multiple_of(b) => Some(vec![result / b]),
+            _ => Some(vec![]),
+        }
+    }
+}
+
+impl Operator for Divide {
+    fn symbol(&self) -> String {
+        "/".into()
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        a.checked_div(b)
+    }
+
+    fn undo(&self, result: u128, b: u128) -> Option<Vec<u128>> {
+        if b > MAX_PREIMAGES {
+            return None;
+        }
+        match result.checked_mul(b) {
+            Some(lo) if b > 0 => Some((lo..=lo.saturating_add(b - 1)).collect()),
+            _ => Some(vec![]),
+        }
+    }
+}
+
+impl Operator for Concat {
+    fn symbol(&self) -> String {
+        if self.base == 10 {
+            "||".into()
+        } else {
+            format!("||{}", self.base)
+        }
+    }
+
+    fn apply(&self, a: u128, b: u128) -> Option<u128> {
+        let digits = b.checked_ilog(self.base).unwrap_or(0) + 1;
+        a.checked_mul(self.base.checked_pow(digits)?)?
+            .checked_add(b)
+    }
+
+    fn undo(&self, result: u128, b: u128) -> Option<Vec<u128>> {
+        let digits = b.checked_ilog(self.base).unwrap_or(0) + 1;
+        let Some(scale) = self.base.checked_pow(digits) else {
+            return Some(vec![]);
+        };
+        match result.checked_sub(b) {
+            Some(rest) if rest.is_multiple_of(scale) => Some(vec![rest / scale]),
+            _ => Some(vec![]),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Operators {
+    operators: Vec<Box<dyn Operator>>,
+}
+
+impl Operators {
+    pub fn new(operators: Vec<Box<dyn Operator>>) -> Self {
+        Operators { operators }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Operator> {
+        self.operators.iter().map(|op| op.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.operators.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.operators.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> &dyn Operator {
+        self.operators[index].as_ref()
+    }
+}
+
+// Operator symbols separated by spaces or commas, e.g. "+ * ||" or "+,-,||2".
+impl FromStr for Operators {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut operators: Vec<Box<dyn Operator>> = Vec::new();
+        for symbol in s.split([' ', ',']).filter(|symbol| !symbol.is_empty()) {
+            operators.push(match symbol {
+                "+" => Box::new(Add),
+                "-" => Box::new(Subtract),
+                "*" => Box::new(Multiply),
+                "/" => Box::new(Divide),
+                _ => {
+                    let Some(base) = symbol.strip_prefix("||") else {
+                        bail!("Unknown operator: {}", symbol);
+                    };
+                    let base = if base.is_empty() { 10 } else { base.parse()? };
+                    ensure!(base >= 2, "Invalid base {}", base);
+                    Box::new(Concat { base })
+                }
+            });
+        }
+        ensure!(!operators.is_empty(), "No operators");
+        Ok(Operators::new(operators))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() -> Result<()> {
+        let operators: Operators = "+, -  * / || ||2".parse()?;
+        let symbols: Vec<String> = operators.iter().map(|op| op.symbol()).collect();
+        assert_eq!(symbols, ["+", "-", "*", "/", "||", "||2"]);
+        assert!("%".parse::<Operators>().is_err());
+        assert!("||1".parse::<Operators>().is_err());
+        assert!("".parse::<Operators>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn apply() {
+        assert_eq!(Subtract.apply(3, 5), None);
+        assert_eq!(Divide.apply(7, 2), Some(3));
+        assert_eq!(Divide.apply(7, 0), None);
+        assert_eq!(Concat { base: 10 }.apply(12, 345), Some(12345));
+        assert_eq!(Concat { base: 10 }.apply(12, 0), Some(120));
+        // 0b101 || 0b11 = 0b10111
+        assert_eq!(Concat { base: 2 }.apply(5, 3), Some(23));
+        assert_eq!(Multiply.apply(u128::MAX, 2), None);
+    }
+
+    #[test]
+    fn undo() {
+        assert_eq!(Add.undo(5, 7), Some(vec![]));
+        assert_eq!(Subtract.undo(5, 7), Some(vec![12]));
+        assert_eq!(Multiply.undo(12, 5), Some(vec![]));
+        assert_eq!(Multiply.undo(0, 0), None);
+        assert_eq!(Divide.undo(3, 2), Some(vec![6, 7]));
+        assert_eq!(Divide.undo(3, 0), Some(vec![]));
+        assert_eq!(Divide.undo(3, 1000), None);
+        assert_eq!(Concat { base: 10 }.undo(12345, 345), Some(vec![12]));
+        assert_eq!(Concat { base: 10 }.undo(120, 0), Some(vec![12]));
+        assert_eq!(Concat { base: 10 }.undo(12345, 45), Some(vec![123]));
+        assert_eq!(Concat { base: 10 }.undo(12345, 44), Some(vec![]));
+        assert_eq!(Concat { base: 10 }.undo(5, 345), Some(vec![]));
+        for (op, b) in [
+            (&Add as &dyn Operator, 3),
+            (&Subtract, 3),
+            (&Multiply, 3),
+            (&Divide, 3),
+            (&Concat { base: 2 }, 3),
+        ] {
+            for a in 0..100 {
+                if let Some(result) = op.apply(a, b) {
+                    assert!(op.undo(result, b).unwrap().contains(&a), "{:?} {}", op, a);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Write,
+    str::FromStr,
+};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+
+pub type Position = (i64, i64);
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub height: i64,
+    pub width: i64,
+    pub cells: Vec<Vec<char>>,
+    pub antennas: BTreeMap<char, Vec<Position>>,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let cells: Vec<Vec<char>> = input.lines().map(|line| line.chars().collect()).collect();
+        ensure!(!cells.is_empty(), "No lines");
+
+        let width = cells[0].len();
+        let mut antennas: BTreeMap<char, Vec<Position>> = BTreeMap::new();
+        for (i, row) in cells.iter().enumerate() {
+            ensure!(
+                row.len() == width,
+                "Mismatched line length at {} (got {}, want {})",
+                i + 1,
+                row.len(),
+                width
+            );
+            for (j, &c) in row.iter().enumerate() {
+                if c != '.' {
+                    antennas.entry(c).or_default().push((i as i64, j as i64));
+                }
+            }
+        }
+
+        Ok(Problem {
+            height: cells.len() as i64,
+            width: width as i64,
+            cells,
+            antennas,
+        })
+    }
+}
+
+// Multiples k of the distance between two antennas at which antinodes
+// appear beyond the second one. Part a uses 1..1 and part b uses 0.. .
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Harmonics {
+    pub min: i64,
+    pub max: Option<i64>,
+}
+
+impl FromStr for Harmonics {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let harmonics = if let Some((lo, hi)) = s.split_once("..") {
+            Harmonics {
+                min: lo.parse()?,
+                max: if hi.is_empty() {
+                    None
+                } else {
+                    Some(hi.parse()?)
+                },
+            }
+        } else {
+            let k = s.parse()?;
+            Harmonics {
+                min: k,
+                max: Some(k),
+            }
+        };
+        ensure!(
+            harmonics.max.is_none_or(|max| max >= harmonics.min),
+            "Empty harmonic range: {}",
+            s
+        );
+        Ok(harmonics)
+    }
+}
+
+// Values of k for which p + k * d stays within 0..size.
+fn bound_range(p: i64, d: i64, size: i64) -> (i64, i64) {
+    match d {
+        0 if (0..size).contains(&p) => (i64::MIN, i64::MAX),
+        0 => (1, 0),
+        _ if d > 0 => (-p.div_euclid(d), (size - 1 - p).div_euclid(d)),
+        _ => {
+            let (lo, hi) = bound_range(p, -d, size);
+            (-hi, -lo)
+        }
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a.abs()
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Engine<'a> {
+    pub problem: &'a Problem,
+    pub harmonics: Harmonics,
+    pub divisions: Option<i64>,
+}
+
+impl Engine<'_> {
+    // Antinodes of a pair: the harmonics beyond b, plus the points strictly
+    // between a and b at whole multiples of 1/divisions of the way.
+    fn pair_antinodes(&self, a: Position, b: Position, antinodes: &mut BTreeSet<Position>) {
+        let d = (b.0 - a.0, b.1 - a.1);
+        let (lo_i, hi_i) = bound_range(b.0, d.0, self.problem.height);
+        let (lo_j, hi_j) = bound_range(b.1, d.1, self.problem.width);
+        let lo = lo_i.max(lo_j).max(self.harmonics.min);
+        let hi = hi_i.min(hi_j).min(self.harmonics.max.unwrap_or(i64::MAX));
+        for k in lo..=hi {
+            antinodes.insert((b.0 + k * d.0, b.1 + k * d.1));
+        }
+
+        // Only multiples of 1/g land on whole cells, g = gcd(n, d.0, d.1).
+        if let Some(n) = self.divisions {
+            let g = gcd(n, gcd(d.0, d.1));
+            for m in 1..g {
+                antinodes.insert((a.0 + m * (d.0 / g), a.1 + m * (d.1 / g)));
+            }
+        }
+    }
+
+    pub fn antinodes(&self, antennas: &[Position]) -> BTreeSet<Position> {
+        let mut antinodes = BTreeSet::new();
+        for (i, &a) in antennas.iter().enumerate() {
+            for (j, &b) in antennas.iter().enumerate() {
+                if i != j {
+                    self.pair_antinodes(a, b, &mut antinodes);
+                }
+            }
+        }
+        antinodes
+    }
+
+    pub fn by_frequency(&self) -> BTreeMap<char, BTreeSet<Position>> {
+        self.problem
+            .antennas
+            .iter()
+            .map(|(&freq, antennas)| (freq, self.antinodes(antennas)))
+            .collect()
+    }
+
+    pub fn all(&self) -> BTreeSet<Position> {
+        self.by_frequency().into_values().flatten().collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Count,
+    Report,
+    Map,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub harmonics: Harmonics,
+    pub divisions: Option<i64>,
+    pub mode: Mode,
+}
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--harmonics" => {
+                    self.harmonics = args.next().context("Missing --harmonics value")?.parse()?;
+                }
+                "--divisions" => {
+                    let n: i64 = args.next().context("Missing --divisions value")?.parse()?;
+                    ensure!(n >= 2, "Divisions must be at least 2");
+                    self.divisions = Some(n);
+                }
+                "--report" => self.mode = Mode::Report,
+                "--map" => self.mode = Mode::Map,
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+
+    fn engine<'a>(&self, problem: &'a Problem) -> Engine<'a> {
+        Engine {
+            problem,
+            harmonics: self.harmonics,
+            divisions: self.divisions,
+        }
+    }
+}
+
+// Antinodes are drawn only over empty cells so the antennas stay visible.
+pub fn annotate(problem: &Problem, antinodes: &BTreeSet<Position>) -> String {
+    let mut cells = problem.cells.clone();
+    for &(i, j) in antinodes {
+        let cell = &mut cells[i as usize][j as usize];
+        if *cell == '.' {
+            *cell = '#';
+        }
+    }
+    cells
+        .into_iter()
+        .map(|row| row.into_iter().chain(['\n']).collect::<String>())
+        .collect()
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<usize> {
+    Ok(options.engine(problem).all().len())
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let engine = options.engine(problem);
+    let mut output = String::new();
+    match options.mode {
+        Mode::Count => writeln!(output, "{}", solve(problem, options)?)?,
+        Mode::Report => {
+            let by_frequency = engine.by_frequency();
+            for (freq, antinodes) in &by_frequency {
+                writeln!(output, "{}: {}", freq, antinodes.len())?;
+            }
+            for (a, a_antinodes) in &by_frequency {
+                for (b, b_antinodes) in by_frequency.range(a..).skip(1) {
+                    let shared = a_antinodes.intersection(b_antinodes).count();
+                    if shared > 0 {
+                        writeln!(output, "{} & {}: {}", a, b, shared)?;
+                    }
+                }
+            }
+        }
+        Mode::Map => output.push_str(&annotate(problem, &engine.all())),
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "............
+........0...
+.....0......
+.......0....
+....0.......
+......A.....
+............
+............
+........A...
+.........A..
+............
+............
+";
+
+    fn options(harmonics: &str, divisions: Option<i64>, mode: Mode) -> Result<Options> {
+        Ok(Options {
+            harmonics: harmonics.parse()?,
+            divisions,
+            mode,
+        })
+    }
+
+    #[test]
+    fn harmonics() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        assert_eq!(solve(&problem, &options("1", None, Mode::Count)?)?, 14);
+        assert_eq!(solve(&problem, &options("0..", None, Mode::Count)?)?, 34);
+        assert_eq!(solve(&problem, &options("0..100", None, Mode::Count)?)?, 34);
+        // k = 0 puts an antinode on every antenna of a multi-antenna frequency.
+        assert_eq!(solve(&problem, &options("0", None, Mode::Count)?)?, 7);
+        assert!("2..1".parse::<Harmonics>().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn divisions() -> Result<()> {
+        // The antennas are 3 apart, so thirds fall on the two cells between.
+        let problem: Problem = ".a..a.\n".parse()?;
+        let engine = options("1", Some(3), Mode::Count)?.engine(&problem);
+        assert_eq!(engine.all(), [(0, 2), (0, 3)].into());
+        let engine = options("1", Some(2), Mode::Count)?.engine(&problem);
+        assert!(engine.all().is_empty());
+        // Huge divisions only visit the lattice points.
+        let engine = options("1", Some(3 << 40), Mode::Count)?.engine(&problem);
+        assert_eq!(engine.all(), [(0, 2), (0, 3)].into());
+        let engine = options("1", Some(i64::MAX), Mode::Count)?.engine(&problem);
+        assert!(engine.all().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn report() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        let output = run(&problem, &options("1", None, Mode::Report)?)?;
+        assert_eq!(output, "0: 10\nA: 5\n0 & A: 1\n");
+        Ok(())
+    }
+
+    #[test]
+    fn map() -> Result<()> {
+        let problem: Problem = "T.........
+...T......
+.T........
+..........
+..........
+..........
+..........
+..........
+..........
+..........
+"
+        .parse()?;
+        let output = run(&problem, &options("0..", None, Mode::Map)?)?;
+        assert_eq!(
+            output,
+            "T....#....
+...T......
+.T....#...
+.........#
+..#.......
+..........
+...#......
+..........
+....#.....
+..........
+"
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,500 @@
+use std::{cmp::Reverse, collections::BinaryHeap, fmt::Write, str::FromStr};
+
+use anyhow::{bail, ensure, Context, Error, Result};
+
+#[derive(Clone, Debug)]
+pub struct Block {
+    pub id: u64,
+    pub file_len: u64,
+    pub free_len: u64,
+}
+
+#[derive(Clone, Debug)]
+pub struct Problem {
+    pub blocks: Vec<Block>,
+}
+
+impl FromStr for Problem {
+    type Err = Error;
+
+    fn from_str(input: &str) -> Result<Self> {
+        let chars: Vec<char> = input.chars().collect();
+        ensure!(chars.len() % 2 == 1, "Even number of characters");
+
+        let mut blocks: Vec<Block> = Vec::new();
+        for i in (0..chars.len()).step_by(2) {
+            let id = (i / 2) as u64;
+            let file_len: u64 = chars[i].to_digit(10).context("Not a digit")? as u64;
+            ensure!(file_len > 0, "Zero file length");
+            let free_len: u64 = chars
+                .get(i + 1)
+                .unwrap_or(&'0')
+                .to_digit(10)
+                .context("Not a digit")? as u64;
+            blocks.push(Block {
+                id,
+                file_len,
+                free_len,
+            });
+        }
+
+        Ok(Problem { blocks })
+    }
+}
+
+impl Problem {
+    pub fn size(&self) -> u64 {
+        self.blocks
+            .iter()
+            .map(|block| block.file_len + block.free_len)
+            .sum()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Strategy {
+    // Moves single blocks from the end into the leftmost free block (part a).
+    Blocks,
+    // Moves whole files into the leftmost span that fits (part b).
+    FirstFit,
+    // Moves whole files into the smallest span that fits.
+    BestFit,
+    // Moves whole files into the largest span.
+    WorstFit,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 4] = [
+        Strategy::Blocks,
+        Strategy::FirstFit,
+        Strategy::BestFit,
+        Strategy::WorstFit,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Strategy::Blocks => "blocks",
+            Strategy::FirstFit => "first-fit",
+            Strategy::BestFit => "best-fit",
+            Strategy::WorstFit => "worst-fit",
+        }
+    }
+}
+
+impl FromStr for Strategy {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Strategy::ALL
+            .into_iter()
+            .find(|strategy| strategy.name() == s)
+            .with_context(|| format!("Unknown strategy: {}", s))
+    }
+}
+
+// A contiguous run of blocks belonging to one file.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Extent {
+    pub id: u64,
+    pub start: u64,
+    pub len: u64,
+}
+
+// Disk contents after compaction. Extents are sorted by start and everything
+// not covered by them is free.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Layout {
+    pub extents: Vec<Extent>,
+    pub size: u64,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Metrics {
+    pub free_spans: usize,
+    pub largest_free_run: u64,
+}
+
+fn compact_blocks(problem: &Problem) -> Vec<Extent> {
+    let blocks = &problem.blocks;
+    let mut remaining: Vec<u64> = blocks.iter().map(|block| block.file_len).collect();
+    let mut extents = Vec::new();
+    let mut offset = 0;
+    let (mut left, mut right) = (0, blocks.len() - 1);
+    while left <= right {
+        if remaining[left] > 0 {
+            extents.push(Extent {
+                id: blocks[left].id,
+                start: offset,
+                len: remaining[left],
+            });
+            offset += remaining[left];
+            remaining[left] = 0;
+        }
+        let mut free = blocks[left].free_len;
+        while free > 0 && right > left {
+            let len = free.min(remaining[right]);
+            extents.push(Extent {
+                id: blocks[right].id,
+                start: offset,
+                len,
+            });
+            offset += len;
+            free -= len;
+            remaining[right] -= len;
+            if remaining[right] == 0 {
+                right -= 1;
+            }
+        }
+        left += 1;
+    }
+    extents
+}
+
+// Files are tried once each from the highest id down, and only ever move
+// left. A span freed by a move therefore lies right of every file still to
+// be tried and is never reused, so the spans are kept in one min-heap of
+// start offsets per span length.
+fn compact_files(problem: &Problem, strategy: Strategy) -> Vec<Extent> {
+    let mut spans: [BinaryHeap<Reverse<u64>>; 10] = Default::default();
+    let mut starts = Vec::with_capacity(problem.blocks.len());
+    let mut offset = 0;
+    for block in &problem.blocks {
+        starts.push(offset);
+        offset += block.file_len;
+        if block.free_len > 0 {
+            spans[block.free_len as usize].push(Reverse(offset));
+        }
+        offset += block.free_len;
+    }
+
+    let mut extents = Vec::with_capacity(problem.blocks.len());
+    for (block, &start) in problem.blocks.iter().zip(&starts).rev() {
+        let len = block.file_len;
+        // (start, length) of the leftmost span of each length that fits.
+        let mut candidates = (len as usize..spans.len())
+            .filter_map(|span_len| spans[span_len].peek().map(|&Reverse(s)| (s, span_len)))
+            .filter(|&(span_start, _)| span_start < start);
+        let chosen = match strategy {
+            Strategy::FirstFit => candidates.min(),
+            Strategy::BestFit => candidates.next(),
+            Strategy::WorstFit => candidates.next_back(),
+            Strategy::Blocks => unreachable!(),
+        };
+        let start = match chosen {
+            Some((span_start, span_len)) => {
+                spans[span_len].pop();
+                let rest = span_len - len as usize;
+                if rest > 0 {
+                    spans[rest].push(Reverse(span_start + len));
+                }
+                span_start
+            }
+            None => start,
+        };
+        extents.push(Extent {
+            id: block.id,
+            start,
+            len,
+        });
+    }
+    extents.sort_by_key(|extent| extent.start);
+    extents
+}
+
+impl Layout {
+    pub fn new(problem: &Problem, strategy: Strategy) -> Layout {
+        let extents = match strategy {
+            Strategy::Blocks => compact_blocks(problem),
+            _ => compact_files(problem, strategy),
+        };
+        Layout {
+            extents,
+            size: problem.size(),
+        }
+    }
+
+    pub fn checksum(&self) -> u128 {
+        self.extents
+            .iter()
+            .map(|extent| {
+                // id * (start + (start + 1) + ... + (start + len - 1))
+                let (id, start, len) =
+                    (extent.id as u128, extent.start as u128, extent.len as u128);
+                id * (len * start + len * (len - 1) / 2)
+            })
+            .sum()
+    }
+
+    // (start, length) of each maximal free run, including the one at the end
+    // of the disk.
+    pub fn free_runs(&self) -> Vec<(u64, u64)> {
+        let mut runs = Vec::new();
+        let mut offset = 0;
+        for extent in &self.extents {
+            if extent.start > offset {
+                runs.push((offset, extent.start - offset));
+            }
+            offset = extent.start + extent.len;
+        }
+        if self.size > offset {
+            runs.push((offset, self.size - offset));
+        }
+        runs
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        let runs = self.free_runs();
+        Metrics {
+            free_spans: runs.len(),
+            largest_free_run: runs.iter().map(|&(_, len)| len).max().unwrap_or(0),
+        }
+    }
+
+    // One character per block as in the puzzle text. Ids beyond 9 continue
+    // with letters, and '#' is used once those run out too.
+    pub fn render(&self) -> String {
+        let mut line = vec!['.'; self.size as usize];
+        for extent in &self.extents {
+            let c = u32::try_from(extent.id)
+                .ok()
+                .and_then(|id| char::from_digit(id, 36))
+                .unwrap_or('#');
+            line[extent.start as usize..(extent.start + extent.len) as usize].fill(c);
+        }
+        line.into_iter().collect()
+    }
+
+    // Run-length encoded layout, e.g. "0x2 9x2 8x1 .x3", merging adjacent
+    // pieces of the same file.
+    pub fn summary(&self) -> String {
+        let mut runs: Vec<(Option<u64>, u64)> = Vec::new();
+        let free = self
+            .free_runs()
+            .into_iter()
+            .map(|(start, len)| (start, None, len));
+        let files = self
+            .extents
+            .iter()
+            .map(|extent| (extent.start, Some(extent.id), extent.len));
+        let mut pieces: Vec<(u64, Option<u64>, u64)> = free.chain(files).collect();
+        pieces.sort();
+        for (_, id, len) in pieces {
+            match runs.last_mut() {
+                Some((last, total)) if *last == id => *total += len,
+                _ => runs.push((id, len)),
+            }
+        }
+        runs.into_iter()
+            .map(|(id, len)| match id {
+                Some(id) => format!("{}x{}", id, len),
+                None => format!(".x{}", len),
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    Checksum,
+    Show,
+    Metrics,
+    Compare,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Options {
+    pub strategy: Strategy,
+    pub mode: Mode,
+}
+
+// Disks larger than this are shown as a run-length summary.
+const RENDER_LIMIT: u64 = 200;
+
+impl Options {
+    pub fn parse_args(mut self, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--strategy" => {
+                    self.strategy = args.next().context("Missing --strategy value")?.parse()?;
+                }
+                "--show" => self.mode = Mode::Show,
+                "--metrics" => self.mode = Mode::Metrics,
+                "--compare" => self.mode = Mode::Compare,
+                _ => bail!("Unknown argument: {}", arg),
+            }
+        }
+        Ok(self)
+    }
+}
+
+pub fn solve(problem: &Problem, options: &Options) -> Result<u128> {
+    Ok(Layout::new(problem, options.strategy).checksum())
+}
+
+pub fn run(problem: &Problem, options: &Options) -> Result<String> {
+    let mut output = String::new();
+    match options.mode {
+        Mode::Checksum => writeln!(output, "{}", solve(problem, options)?)?,
+        Mode::Show => {
+            let layout = Layout::new(problem, options.strategy);
+            if layout.size <= RENDER_LIMIT {
+                writeln!(output, "{}", layout.render())?;
+            } else {
+                writeln!(output, "{}", layout.summary())?;
+            }
+        }
+        Mode::Metrics => {
+            let metrics = Layout::new(problem, options.strategy).metrics();
+            writeln!(output, "free spans: {}", metrics.free_spans)?;
+            writeln!(output, "largest free run: {}", metrics.largest_free_run)?;
+        }
+        Mode::Compare => {
+            for strategy in Strategy::ALL {
+                let layout = Layout::new(problem, strategy);
+                let metrics = layout.metrics();
+                writeln!(
+                    output,
+                    "{:<10} {:>20} {:>8} {:>8}",
+                    strategy.name(),
+                    layout.checksum(),
+                    metrics.free_spans,
+                    metrics.largest_free_run
+                )?;
+            }
+        }
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testutil::Lcg;
+
+    const SAMPLE: &str = "2333133121414131402";
+
+    #[test]
+    fn layouts() -> Result<()> {
+        let problem: Problem = SAMPLE.parse()?;
+        let blocks = Layout::new(&problem, Strategy::Blocks);
+        assert_eq!(
+            blocks.render(),
+            "0099811188827773336446555566.............."
+        );
+        assert_eq!(blocks.checksum(), 1928);
+        assert_eq!(
+            blocks.metrics(),
+            Metrics {
+                free_spans: 1,
+                largest_free_run: 14,
+            }
+        );
+
+        let first_fit = Layout::new(&problem, Strategy::FirstFit);
+        assert_eq!(
+            first_fit.render(),
+            "00992111777.44.333....5555.6666.....8888.."
+        );
+        assert_eq!(first_fit.checksum(), 2858);
+        assert_eq!(
+            first_fit.metrics(),
+            Metrics {
+                free_spans: 6,
+                largest_free_run: 5,
+            }
+        );
+        assert_eq!(
+            first_fit.summary(),
+            "0x2 9x2 2x1 1x3 7x3 .x1 4x2 .x1 3x3 .x4 5x4 .x1 6x4 .x5 8x4 .x2"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn fits() -> Result<()> {
+        let render = |input: &str, strategy| -> Result<String> {
+            Ok(Layout::new(&input.parse()?, strategy).render())
+        };
+        // A span of 3 blocks followed by one of 2 blocks.
+        assert_eq!(render("13122", Strategy::FirstFit)?, "0221.....");
+        assert_eq!(render("13122", Strategy::BestFit)?, "01...22..");
+        assert_eq!(render("13122", Strategy::WorstFit)?, "0221.....");
+        // A span of 2 blocks followed by one of 3 blocks.
+        assert_eq!(render("12132", Strategy::FirstFit)?, "0221.....");
+        assert_eq!(render("12132", Strategy::BestFit)?, "0221.....");
+        assert_eq!(render("12132", Strategy::WorstFit)?, "01..22...");
+        Ok(())
+    }
+
+    // The original quadratic defragmenter, kept as a reference.
+    fn solve_naive(problem: &Problem) -> u64 {
+        let mut blocks = problem.blocks.clone();
+
+        for src_pos in (0..blocks.len()).rev() {
+            loop {
+                let move_file_len = blocks[src_pos].file_len;
+                let mut moved = false;
+                for dst_pos in 0..src_pos {
+                    if blocks[dst_pos].free_len >= move_file_len {
+                        let mut move_block = blocks.remove(src_pos);
+                        blocks[src_pos - 1].free_len += move_block.file_len + move_block.free_len;
+                        move_block.free_len = blocks[dst_pos].free_len - move_block.file_len;
+                        blocks[dst_pos].free_len = 0;
+                        blocks.insert(dst_pos + 1, move_block);
+                        moved = true;
+                        break;
+                    }
+                }
+                if !moved {
+                    break;
+                }
+            }
+        }
+
+        let mut checksum = 0;
+        let mut offset = 0;
+        for block in &blocks {
+            for _ in 0..block.file_len {
+                checksum += offset * block.id;
+                offset += 1;
+            }
+            offset += block.free_len;
+        }
+
+        checksum
+    }
+
+    fn random_disk_map(digits: usize, seed: u64) -> String {
+        let mut rng = Lcg(seed);
+        (0..digits)
+            .map(|i| {
+                let digit = rng.next(10);
+                let digit = if i % 2 == 0 { digit.max(1) } else { digit };
+                char::from_digit(digit as u32, 10).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_naive() -> Result<()> {
+        for seed in 0..20 {
+            let problem: Problem = random_disk_map(1001, seed).parse()?;
+            assert_eq!(
+                Layout::new(&problem, Strategy::FirstFit).checksum(),
+                solve_naive(&problem) as u128
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn large() -> Result<()> {
+        let problem: Problem = random_disk_map(1_000_001, 1).parse()?;
+        for strategy in Strategy::ALL {
+            assert!(Layout::new(&problem, strategy).checksum() > 0);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,16 @@
+pub mod day10;
+pub mod day11;
+pub mod day12;
+pub mod day13;
+pub mod day14;
+pub mod day15;
+pub mod day20;
+pub mod day23;
+pub mod day6;
+pub mod day7;
+pub mod day8;
+pub mod day9;
+pub mod parallel;
+
+#[cfg(test)]
+mod testutil;
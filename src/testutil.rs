@@ -0,0 +1,12 @@
+// Deterministic pseudo-random numbers for generated test cases.
+pub struct Lcg(pub u64);
+
+impl Lcg {
+    pub fn next(&mut self, modulus: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        (self.0 >> 33) % modulus
+    }
+}